@@ -1,5 +1,4 @@
-use super::{DomSystems, html::Element};
-use crate::js_err::JsErr;
+use super::{DomSystems, backend::DomBackend};
 use bevy_app::prelude::*;
 use bevy_ecs::{
     component::ComponentId,
@@ -12,19 +11,15 @@ use bevy_platform::collections::HashMap;
 use bevy_query_observer::{AddStopObserver, Stop};
 use std::borrow::Cow;
 
-pub(super) struct AttributePlugin;
-
-impl Plugin for AttributePlugin {
-    fn build(&self, app: &mut App) {
-        app.add_plugins(Data::plugin);
-        app.add_plugins(Download::plugin);
-        app.add_systems(
-            PostUpdate,
-            update_attributes
-                .before(DomSystems::Reparent)
-                .after(DomSystems::Insert),
-        );
-    }
+pub(super) fn plugin<B: DomBackend>(app: &mut App) {
+    Data::plugin::<B>(app);
+    Download::plugin::<B>(app);
+    app.add_systems(
+        PostUpdate,
+        update_attributes::<B>
+            .before(DomSystems::Reparent)
+            .after(DomSystems::Insert),
+    );
 }
 
 macro_rules! attribute {
@@ -37,8 +32,12 @@ macro_rules! attribute {
         pub struct $ty(Cow<'static, str>);
 
         impl Attribute for $ty {
-            fn set(&self, element: &Element) -> Result {
-                element.set_attribute($attr, &self.0).js_err()
+            fn name(&self) -> &'static str {
+                $attr
+            }
+
+            fn value(&self) -> Cow<'_, str> {
+                Cow::Borrowed(&self.0)
             }
         }
 
@@ -84,36 +83,30 @@ macro_rules! attribute {
                 self.0.to_mut()
             }
 
-            fn replace(mut world: DeferredWorld, context: HookContext) {
-                world
-                    .commands()
-                    .entity(context.entity)
-                    .entry::<Attributes>()
-                    .or_default()
-                    .and_modify(move |mut attr| {
-                        attr.remove(context.component_id, Cow::from($attr));
-                    });
+            fn replace(world: DeferredWorld, context: HookContext) {
+                replace_hook(world, context, $attr);
             }
         }
     };
 }
 
-trait Attribute {
-    fn set(&self, element: &Element) -> Result;
+/// An attribute component, as a name-value pair.
+pub(crate) trait Attribute {
+    fn name(&self) -> &'static str;
+    fn value(&self) -> Cow<'_, str>;
 }
 
 #[derive(Component, Default)]
-struct Attributes {
+pub(crate) struct Attributes {
     attributes: Vec<(ComponentId, AttributeThunk)>,
     removed: HashMap<ComponentId, Cow<'static, str>>,
 }
 
-type AttributeThunk =
-    for<'a> fn(&'a EntityRefExcept<(Attributes, Element)>) -> Option<&'a dyn Attribute>;
+type AttributeThunk = for<'a> fn(&'a EntityRefExcept<Attributes>) -> Option<&'a dyn Attribute>;
 
 impl Attributes {
     pub fn insert<T: Component + Attribute>(&mut self, id: ComponentId) {
-        self.insert_thunk(id, |entity: &EntityRefExcept<(Attributes, Element)>| {
+        self.insert_thunk(id, |entity: &EntityRefExcept<Attributes>| {
             entity.get::<T>().map(|t| t as &dyn Attribute)
         });
     }
@@ -127,6 +120,37 @@ impl Attributes {
         self.attributes.retain(|a| a.0 != id);
         self.removed.insert(id, name);
     }
+
+    /// Take the names of attributes removed since the last drain.
+    pub(crate) fn drain_removed(&mut self) -> impl Iterator<Item = Cow<'static, str>> + '_ {
+        self.removed.drain().map(|(_, name)| name)
+    }
+
+    /// Every attribute on `entity`, in insertion order.
+    pub(crate) fn iter<'a>(
+        &'a self,
+        entity: &'a EntityRefExcept<Attributes>,
+    ) -> impl Iterator<Item = &'a dyn Attribute> + 'a {
+        self.attributes
+            .iter()
+            .filter_map(move |(_, thunk)| thunk(entity))
+    }
+
+    /// The attributes on `entity` whose component changed between `last_run`
+    /// and `this_run`.
+    pub(crate) fn changed<'a>(
+        &'a self,
+        entity: &'a EntityRefExcept<Attributes>,
+        ticks: &'a SystemChangeTick,
+    ) -> impl Iterator<Item = &'a dyn Attribute> + 'a {
+        self.attributes.iter().filter_map(move |(id, thunk)| {
+            entity
+                .get_change_ticks_by_id(*id)
+                .is_some_and(|t| t.is_changed(ticks.last_run(), ticks.this_run()))
+                .then(|| thunk(entity))
+                .flatten()
+        })
+    }
 }
 
 fn insert_hook<T: Component + Attribute>(mut world: DeferredWorld, context: HookContext) {
@@ -140,27 +164,39 @@ fn insert_hook<T: Component + Attribute>(mut world: DeferredWorld, context: Hook
         });
 }
 
-fn update_attributes(
+/// Forget a replaced attribute. The entity may be despawning, in which case
+/// there's nothing left to update.
+fn replace_hook(mut world: DeferredWorld, context: HookContext, name: &'static str) {
+    world
+        .commands()
+        .entity(context.entity)
+        .queue_silenced(move |mut entity: EntityWorldMut| {
+            entity
+                .entry::<Attributes>()
+                .or_default()
+                .into_mut()
+                .remove(context.component_id, Cow::from(name));
+        });
+}
+
+fn update_attributes<B: DomBackend>(
     mut attributes: Query<(
         &mut Attributes,
-        &Element,
-        EntityRefExcept<(Attributes, Element)>,
+        &B::NodeComponent,
+        EntityRefExcept<Attributes>,
     )>,
+    mut backend: ResMut<B>,
     ticks: SystemChangeTick,
 ) -> Result {
-    for (mut attributes, element, entity) in &mut attributes {
-        for (_, attr) in attributes.removed.drain() {
-            element.remove_attribute(&attr).js_err()?;
+    for (mut attributes, node, entity) in &mut attributes {
+        let node = B::node(node);
+
+        for attr in attributes.drain_removed() {
+            backend.remove_attribute(node, &attr)?;
         }
 
-        for (id, thunk) in &attributes.attributes {
-            if entity
-                .get_change_ticks_by_id(*id)
-                .is_some_and(|t| t.is_changed(ticks.last_run(), ticks.this_run()))
-                && let Some(attr) = thunk(&entity)
-            {
-                attr.set(element)?;
-            }
+        for attr in attributes.changed(&entity, &ticks) {
+            backend.set_attribute(node, attr.name(), &attr.value())?;
         }
     }
 
@@ -183,6 +219,7 @@ attribute! {Fill, "fill"}
 attribute! {Type, "type"}
 attribute! {Accept, "accept"}
 attribute! {AccessKey, "accesskey"}
+attribute! {AriaCurrent, "aria-current"}
 attribute! {Action, "action"}
 attribute! {Allow, "allow"}
 attribute! {Alt, "alt"}
@@ -238,21 +275,18 @@ macro_rules! boolean_attribute {
         pub struct $ty;
 
         impl Attribute for $ty {
-            fn set(&self, element: &Element) -> Result {
-                element.set_attribute($attr, "").js_err()
+            fn name(&self) -> &'static str {
+                $attr
+            }
+
+            fn value(&self) -> Cow<'_, str> {
+                Cow::Borrowed("")
             }
         }
 
         impl $ty {
-            fn replace(mut world: DeferredWorld, context: HookContext) {
-                world
-                    .commands()
-                    .entity(context.entity)
-                    .entry::<Attributes>()
-                    .or_default()
-                    .and_modify(move |mut attr| {
-                        attr.remove(context.component_id, Cow::from($attr));
-                    });
+            fn replace(world: DeferredWorld, context: HookContext) {
+                replace_hook(world, context, $attr);
             }
         }
     };
@@ -295,8 +329,12 @@ macro_rules! enum_attribute {
         }
 
         impl Attribute for $ty {
-            fn set(&self, element: &Element) -> Result {
-                element.set_attribute($attr, self.as_attribute()).js_err()
+            fn name(&self) -> &'static str {
+                $attr
+            }
+
+            fn value(&self) -> Cow<'_, str> {
+                Cow::Borrowed(self.as_attribute())
             }
         }
 
@@ -309,15 +347,8 @@ macro_rules! enum_attribute {
                 }
             }
 
-            fn replace(mut world: DeferredWorld, context: HookContext) {
-                world
-                    .commands()
-                    .entity(context.entity)
-                    .entry::<Attributes>()
-                    .or_default()
-                    .and_modify(move |mut attr| {
-                        attr.remove(context.component_id, Cow::from($attr));
-                    });
+            fn replace(world: DeferredWorld, context: HookContext) {
+                replace_hook(world, context, $attr);
             }
         }
     };
@@ -483,22 +514,18 @@ macro_rules! value_attribute {
         pub struct $ty(pub $inner);
 
         impl Attribute for $ty {
-            fn set(&self, element: &Element) -> Result {
-                let value = self.0.to_string();
-                element.set_attribute($attr, &value).js_err()
+            fn name(&self) -> &'static str {
+                $attr
+            }
+
+            fn value(&self) -> Cow<'_, str> {
+                Cow::Owned(self.0.to_string())
             }
         }
 
         impl $ty {
-            fn replace(mut world: DeferredWorld, context: HookContext) {
-                world
-                    .commands()
-                    .entity(context.entity)
-                    .entry::<Attributes>()
-                    .or_default()
-                    .and_modify(move |mut attr| {
-                        attr.remove(context.component_id, Cow::from($attr));
-                    });
+            fn replace(world: DeferredWorld, context: HookContext) {
+                replace_hook(world, context, $attr);
             }
         }
     };
@@ -529,7 +556,10 @@ pub enum Download {
 
 impl Download {
     // TODO: these should really be trait-like
-    fn attach(attrs: Query<(&Self, Option<&Element>), Changed<Self>>) -> Result {
+    fn attach<B: DomBackend>(
+        attrs: Query<(&Self, Option<&B::NodeComponent>), Changed<Self>>,
+        mut backend: ResMut<B>,
+    ) -> Result {
         for (dl, element) in &attrs {
             let Some(element) = element else {
                 return Err("'download' attribute requires an `Element`".into());
@@ -540,23 +570,27 @@ impl Download {
                 Download::Auto => Cow::Borrowed(""),
             };
 
-            element.set_attribute("download", &value).js_err()?;
+            backend.set_attribute(B::node(element), "download", &value)?;
         }
 
         Ok(())
     }
 
-    fn observe_remove(trigger: On<Remove, Self>, attr: Query<&Element>) -> Result {
+    fn observe_remove<B: DomBackend>(
+        trigger: On<Remove, Self>,
+        attr: Query<&B::NodeComponent>,
+        mut backend: ResMut<B>,
+    ) -> Result {
         let Ok(element) = attr.get(trigger.entity) else {
             return Ok(());
         };
 
-        element.remove_attribute("download").js_err()
+        backend.remove_attribute(B::node(element), "download")
     }
 
-    fn plugin(app: &mut App) {
-        app.add_systems(PostUpdate, (Self::attach.in_set(DomSystems::Attach),))
-            .add_observer(Self::observe_remove);
+    fn plugin<B: DomBackend>(app: &mut App) {
+        app.add_systems(PostUpdate, (Self::attach::<B>.in_set(DomSystems::Attach),))
+            .add_observer(Self::observe_remove::<B>);
     }
 }
 
@@ -578,32 +612,40 @@ impl Data {
         }
     }
 
-    fn attribute_string(&self) -> String {
+    pub(crate) fn attribute_string(&self) -> String {
         format!("data-{}", self.name)
     }
 
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
     // TODO: these should really be trait-like
-    fn attach(attrs: Query<(&Self, Option<&Element>), Changed<Self>>) -> Result {
+    fn attach<B: DomBackend>(
+        attrs: Query<(&Self, Option<&B::NodeComponent>), Changed<Self>>,
+        mut backend: ResMut<B>,
+    ) -> Result {
         for (data, element) in &attrs {
             let Some(element) = element else {
                 return Err(format!("'data-{}' attribute requires an `Element`", data.name).into());
             };
 
-            element
-                .set_attribute(&data.attribute_string(), &data.value)
-                .js_err()?;
+            backend.set_attribute(B::node(element), &data.attribute_string(), &data.value)?;
         }
 
         Ok(())
     }
 
-    fn remove(stop: Stop<(&Self, &Element)>) -> Result {
+    fn remove<B: DomBackend>(
+        stop: Stop<(&Self, &B::NodeComponent)>,
+        mut backend: ResMut<B>,
+    ) -> Result {
         let (data, element) = stop.into_inner();
-        element.remove_attribute(&data.attribute_string()).js_err()
+        backend.remove_attribute(B::node(element), &data.attribute_string())
     }
 
-    fn plugin(app: &mut App) {
-        app.add_systems(PostUpdate, (Self::attach.in_set(DomSystems::Attach),))
-            .add_stop_observer(Self::remove);
+    fn plugin<B: DomBackend>(app: &mut App) {
+        app.add_systems(PostUpdate, (Self::attach::<B>.in_set(DomSystems::Attach),))
+            .add_stop_observer(Self::remove::<B>);
     }
 }
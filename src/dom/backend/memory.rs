@@ -0,0 +1,592 @@
+use super::{BackendNode, DomBackend, PropValue};
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::HashMap;
+
+/// The node backing an entity in a [`MemoryDom`].
+pub type MemoryNode = BackendNode<MemoryDom>;
+
+/// A handle to a node in a [`MemoryDom`].
+///
+/// Handles are never reused, so a handle to a removed node stays dangling
+/// rather than aliasing a newer one.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(any(test, feature = "debug"), derive(Debug))]
+pub struct MemoryNodeId(usize);
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(any(test, feature = "debug"), derive(Debug))]
+pub enum MemoryNodeKind {
+    Document,
+    Element(MemoryElement),
+    Text(String),
+}
+
+/// An element in a [`MemoryDom`].
+#[derive(Clone, Default, PartialEq)]
+#[cfg_attr(any(test, feature = "debug"), derive(Debug))]
+pub struct MemoryElement {
+    pub name: String,
+    pub namespace: Option<String>,
+    /// Attributes in insertion order.
+    pub attributes: Vec<(String, String)>,
+    /// Classes in insertion order.
+    pub classes: Vec<String>,
+    pub properties: HashMap<String, PropValue>,
+    /// Raw markup set through `InnerHtml`. It isn't parsed into nodes.
+    pub inner_html: Option<String>,
+}
+
+#[cfg_attr(any(test, feature = "debug"), derive(Debug))]
+struct MemoryNodeData {
+    kind: MemoryNodeKind,
+    parent: Option<MemoryNodeId>,
+    children: Vec<MemoryNodeId>,
+    entity: Option<Entity>,
+}
+
+/// An in-memory document tree.
+///
+/// Install it with [`DomBackendPlugin`](super::DomBackendPlugin) to run `bweb` UI logic natively, then inspect the tree after
+/// `app.update()`.
+#[derive(Resource)]
+#[cfg_attr(any(test, feature = "debug"), derive(Debug))]
+pub struct MemoryDom {
+    nodes: Vec<Option<MemoryNodeData>>,
+}
+
+impl Default for MemoryDom {
+    fn default() -> Self {
+        Self {
+            nodes: vec![Some(MemoryNodeData {
+                kind: MemoryNodeKind::Document,
+                parent: None,
+                children: Vec::new(),
+                entity: None,
+            })],
+        }
+    }
+}
+
+impl MemoryDom {
+    fn node(&self, node: MemoryNodeId) -> Option<&MemoryNodeData> {
+        self.nodes.get(node.0)?.as_ref()
+    }
+
+    fn node_mut(&mut self, node: MemoryNodeId) -> Result<&mut MemoryNodeData> {
+        self.nodes
+            .get_mut(node.0)
+            .and_then(Option::as_mut)
+            .ok_or_else(|| format!("node {} does not exist", node.0).into())
+    }
+
+    fn element_mut(&mut self, node: MemoryNodeId) -> Result<&mut MemoryElement> {
+        match &mut self.node_mut(node)?.kind {
+            MemoryNodeKind::Element(element) => Ok(element),
+            _ => Err(format!("node {} is not an element", node.0).into()),
+        }
+    }
+
+    fn push(&mut self, kind: MemoryNodeKind) -> MemoryNodeId {
+        self.nodes.push(Some(MemoryNodeData {
+            kind,
+            parent: None,
+            children: Vec::new(),
+            entity: None,
+        }));
+
+        MemoryNodeId(self.nodes.len() - 1)
+    }
+
+    fn unlink(&mut self, node: MemoryNodeId) {
+        let Some(parent) = self.node(node).and_then(|n| n.parent) else {
+            return;
+        };
+
+        if let Ok(parent) = self.node_mut(parent) {
+            parent.children.retain(|c| *c != node);
+        }
+        if let Ok(node) = self.node_mut(node) {
+            node.parent = None;
+        }
+    }
+
+    /// Drop `node` and every descendant no entity owns. Owned descendants
+    /// are only detached: their entity may still move them under a new
+    /// parent, and removes them itself otherwise.
+    fn drop_subtree(&mut self, node: MemoryNodeId) {
+        let Some(data) = self.nodes.get_mut(node.0).and_then(Option::take) else {
+            return;
+        };
+
+        for child in data.children {
+            match self.nodes.get_mut(child.0).and_then(Option::as_mut) {
+                Some(child) if child.entity.is_some() => child.parent = None,
+                Some(_) => self.drop_subtree(child),
+                None => {}
+            }
+        }
+    }
+
+    /// The kind and contents of `node`, if it exists.
+    pub fn get(&self, node: MemoryNodeId) -> Option<&MemoryNodeKind> {
+        self.node(node).map(|n| &n.kind)
+    }
+
+    /// The element behind `node`, if it exists and is an element.
+    pub fn element(&self, node: MemoryNodeId) -> Option<&MemoryElement> {
+        match self.get(node)? {
+            MemoryNodeKind::Element(element) => Some(element),
+            _ => None,
+        }
+    }
+
+    pub fn parent(&self, node: MemoryNodeId) -> Option<MemoryNodeId> {
+        self.node(node)?.parent
+    }
+
+    pub fn children(&self, node: MemoryNodeId) -> &[MemoryNodeId] {
+        self.node(node).map_or(&[], |n| &n.children)
+    }
+
+    pub fn attribute(&self, node: MemoryNodeId, name: &str) -> Option<&str> {
+        self.element(node)?
+            .attributes
+            .iter()
+            .find_map(|(n, v)| (n == name).then_some(v.as_str()))
+    }
+
+    pub fn has_class(&self, node: MemoryNodeId, class: &str) -> bool {
+        self.element(node)
+            .is_some_and(|e| e.classes.iter().any(|c| c == class))
+    }
+
+    pub fn property(&self, node: MemoryNodeId, name: &str) -> Option<&PropValue> {
+        self.element(node)?.properties.get(name)
+    }
+
+    /// The concatenated data of every text node under `node`, in document
+    /// order.
+    pub fn text_content(&self, node: MemoryNodeId) -> String {
+        fn collect(dom: &MemoryDom, node: MemoryNodeId, out: &mut String) {
+            if let Some(MemoryNodeKind::Text(text)) = dom.get(node) {
+                out.push_str(text);
+            }
+
+            for child in dom.children(node) {
+                collect(dom, *child, out);
+            }
+        }
+
+        let mut out = String::new();
+        collect(self, node, &mut out);
+        out
+    }
+
+    /// Every element under `node` (inclusive) with the given tag name, in
+    /// document order.
+    pub fn find_by_name(&self, node: MemoryNodeId, name: &str) -> Vec<MemoryNodeId> {
+        fn collect(dom: &MemoryDom, node: MemoryNodeId, name: &str, out: &mut Vec<MemoryNodeId>) {
+            if dom.element(node).is_some_and(|e| e.name == name) {
+                out.push(node);
+            }
+
+            for child in dom.children(node) {
+                collect(dom, *child, name, out);
+            }
+        }
+
+        let mut out = Vec::new();
+        collect(self, node, name, &mut out);
+        out
+    }
+}
+
+impl DomBackend for MemoryDom {
+    type Node = MemoryNodeId;
+    type NodeComponent = MemoryNode;
+
+    fn node(component: &Self::NodeComponent) -> &Self::Node {
+        &component.0
+    }
+
+    fn insert_node(&mut self, entity: &mut EntityCommands, node: Self::Node) {
+        if let Ok(data) = self.node_mut(node) {
+            data.entity = Some(entity.id());
+        }
+
+        entity.insert(BackendNode::<Self>(node));
+    }
+
+    fn remove_node(entity: &mut EntityCommands) {
+        entity.try_remove::<Self::NodeComponent>();
+    }
+
+    fn document(&self) -> Self::Node {
+        MemoryNodeId(0)
+    }
+
+    fn create_element(&mut self, name: &str) -> Result<Self::Node> {
+        Ok(self.push(MemoryNodeKind::Element(MemoryElement {
+            name: name.to_string(),
+            ..Default::default()
+        })))
+    }
+
+    fn create_element_ns(&mut self, namespace: &str, name: &str) -> Result<Self::Node> {
+        Ok(self.push(MemoryNodeKind::Element(MemoryElement {
+            name: name.to_string(),
+            namespace: Some(namespace.to_string()),
+            ..Default::default()
+        })))
+    }
+
+    fn create_text(&mut self, text: &str) -> Result<Self::Node> {
+        Ok(self.push(MemoryNodeKind::Text(text.to_string())))
+    }
+
+    fn set_text(&mut self, node: &Self::Node, text: &str) -> Result {
+        match &mut self.node_mut(*node)?.kind {
+            MemoryNodeKind::Text(data) => {
+                data.clear();
+                data.push_str(text);
+                Ok(())
+            }
+            _ => Err(format!("node {} is not a text node", node.0).into()),
+        }
+    }
+
+    fn parent(&self, node: &Self::Node) -> Option<Self::Node> {
+        self.node(*node)?.parent
+    }
+
+    fn first_child(&self, node: &Self::Node) -> Option<Self::Node> {
+        self.children(*node).first().copied()
+    }
+
+    fn next_sibling(&self, node: &Self::Node) -> Option<Self::Node> {
+        let siblings = self.children(self.node(*node)?.parent?);
+        let index = siblings.iter().position(|c| c == node)?;
+        siblings.get(index + 1).copied()
+    }
+
+    fn local_name(&self, node: &Self::Node) -> Option<String> {
+        self.element(*node).map(|e| e.name.clone())
+    }
+
+    fn text(&self, node: &Self::Node) -> Option<String> {
+        match self.get(*node)? {
+            MemoryNodeKind::Text(text) => Some(text.clone()),
+            _ => None,
+        }
+    }
+
+    fn query_attribute(&self, name: &str, value: &str) -> Option<Self::Node> {
+        fn find(
+            dom: &MemoryDom,
+            node: MemoryNodeId,
+            name: &str,
+            value: &str,
+        ) -> Option<MemoryNodeId> {
+            if dom.attribute(node, name) == Some(value) {
+                return Some(node);
+            }
+
+            dom.children(node)
+                .iter()
+                .find_map(|child| find(dom, *child, name, value))
+        }
+
+        find(self, self.document(), name, value)
+    }
+
+    fn entity(&self, node: &Self::Node) -> Option<Entity> {
+        self.node(*node)?.entity
+    }
+
+    fn insert_before(
+        &mut self,
+        parent: &Self::Node,
+        child: &Self::Node,
+        reference: Option<&Self::Node>,
+    ) -> Result {
+        if matches!(self.node_mut(*parent)?.kind, MemoryNodeKind::Text(_)) {
+            return Err("text nodes cannot have children".into());
+        }
+
+        // Refuse to create a cycle, as the browser does.
+        let mut ancestor = Some(*parent);
+        while let Some(node) = ancestor {
+            if node == *child {
+                return Err("cannot append a node to its own descendant".into());
+            }
+            ancestor = self.parent(node);
+        }
+
+        self.node_mut(*child)?;
+        self.unlink(*child);
+
+        let siblings = &mut self.node_mut(*parent)?.children;
+        let index = match reference {
+            Some(reference) => siblings
+                .iter()
+                .position(|c| c == reference)
+                .ok_or("the reference node is not a child of the parent")?,
+            None => siblings.len(),
+        };
+        siblings.insert(index, *child);
+        self.node_mut(*child)?.parent = Some(*parent);
+
+        Ok(())
+    }
+
+    fn detach(&mut self, node: &Self::Node) -> Result {
+        self.unlink(*node);
+        Ok(())
+    }
+
+    fn remove(&mut self, node: &Self::Node) {
+        self.unlink(*node);
+        self.drop_subtree(*node);
+    }
+
+    fn set_attribute(&mut self, node: &Self::Node, name: &str, value: &str) -> Result {
+        let element = self.element_mut(*node)?;
+
+        match element.attributes.iter_mut().find(|(n, _)| n == name) {
+            Some((_, existing)) => *existing = value.to_string(),
+            None => element
+                .attributes
+                .push((name.to_string(), value.to_string())),
+        }
+
+        Ok(())
+    }
+
+    fn remove_attribute(&mut self, node: &Self::Node, name: &str) -> Result {
+        self.element_mut(*node)?.attributes.retain(|(n, _)| n != name);
+        Ok(())
+    }
+
+    fn add_class(&mut self, node: &Self::Node, class: &str) -> Result {
+        let element = self.element_mut(*node)?;
+        if !element.classes.iter().any(|c| c == class) {
+            element.classes.push(class.to_string());
+        }
+
+        Ok(())
+    }
+
+    fn remove_class(&mut self, node: &Self::Node, class: &str) -> Result {
+        self.element_mut(*node)?.classes.retain(|c| c != class);
+        Ok(())
+    }
+
+    fn set_property(&mut self, node: &Self::Node, name: &str, value: PropValue) -> Result {
+        self.element_mut(*node)?
+            .properties
+            .insert(name.to_string(), value);
+        Ok(())
+    }
+
+    fn set_inner_html(&mut self, node: &Self::Node, html: &str) -> Result {
+        let element = self.element_mut(*node)?;
+        element.inner_html = (!html.is_empty()).then(|| html.to_string());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dom::{backend::DomBackendPlugin, prelude::*};
+    use bevy_app::prelude::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(DomBackendPlugin::<MemoryDom>::default());
+        app.update();
+        app
+    }
+
+    fn node(app: &App, entity: Entity) -> MemoryNodeId {
+        app.world().get::<MemoryNode>(entity).unwrap().0
+    }
+
+    fn body(app: &mut App) -> Entity {
+        app.world_mut()
+            .query_filtered::<Entity, With<Body>>()
+            .single(app.world())
+            .unwrap()
+    }
+
+    #[test]
+    fn initializes_document() {
+        let app = app();
+        let dom = app.world().resource::<MemoryDom>();
+
+        let html = dom.find_by_name(dom.document(), "html");
+        assert_eq!(html.len(), 1);
+
+        let names: Vec<_> = dom
+            .children(html[0])
+            .iter()
+            .map(|c| dom.element(*c).unwrap().name.as_str())
+            .collect();
+        assert_eq!(names, ["head", "body"]);
+    }
+
+    #[test]
+    fn elements_and_text() {
+        let mut app = app();
+        let body = body(&mut app);
+
+        let div = app
+            .world_mut()
+            .spawn((
+                ChildOf(body),
+                Div,
+                attr::Href::new("/home"),
+                class!["card"],
+                children![Text::new("hello"), (Span, children![Text::new(" world")])],
+            ))
+            .id();
+        app.update();
+
+        let div = node(&app, div);
+        let dom = app.world().resource::<MemoryDom>();
+        assert_eq!(dom.element(div).unwrap().name, "div");
+        assert_eq!(dom.attribute(div, "href"), Some("/home"));
+        assert!(dom.has_class(div, "card"));
+        assert_eq!(dom.text_content(div), "hello world");
+    }
+
+    #[test]
+    fn updates_and_removals() {
+        let mut app = app();
+        let body = body(&mut app);
+
+        let text = app.world_mut().spawn(Text::new("before")).id();
+        let div = app
+            .world_mut()
+            .spawn((ChildOf(body), Div, attr::Id::new("a"), attr::Disabled))
+            .add_child(text)
+            .id();
+        app.update();
+
+        app.world_mut().entity_mut(text).insert(Text::new("after"));
+        app.world_mut()
+            .entity_mut(div)
+            .insert(attr::Id::new("b"))
+            .remove::<attr::Disabled>();
+        app.update();
+
+        let div_node = node(&app, div);
+        let dom = app.world().resource::<MemoryDom>();
+        assert_eq!(dom.text_content(div_node), "after");
+        assert_eq!(dom.attribute(div_node, "id"), Some("b"));
+        assert_eq!(dom.attribute(div_node, "disabled"), None);
+
+        app.world_mut().entity_mut(div).despawn();
+        app.update();
+
+        let dom = app.world().resource::<MemoryDom>();
+        assert!(dom.get(div_node).is_none());
+        assert!(dom.find_by_name(dom.document(), "div").is_empty());
+    }
+
+    #[test]
+    fn child_order() {
+        let mut app = app();
+        let body = body(&mut app);
+
+        let a = app.world_mut().spawn((ChildOf(body), Div, attr::Id::new("a"))).id();
+        let b = app.world_mut().spawn((ChildOf(body), Div, attr::Id::new("b"))).id();
+        app.update();
+
+        app.world_mut().entity_mut(body).insert_children(0, &[b]);
+        app.update();
+
+        let body = node(&app, body);
+        let (a, b) = (node(&app, a), node(&app, b));
+        let dom = app.world().resource::<MemoryDom>();
+        assert_eq!(dom.children(body), [b, a]);
+    }
+
+    #[test]
+    fn detaches_orphans() {
+        let mut app = app();
+        let body = body(&mut app);
+
+        let span = app.world_mut().spawn(Span).id();
+        let div = app
+            .world_mut()
+            .spawn((ChildOf(body), Div))
+            .add_child(span)
+            .id();
+        app.update();
+
+        app.world_mut().entity_mut(span).remove::<ChildOf>();
+        app.update();
+
+        let (div, span) = (node(&app, div), node(&app, span));
+        let dom = app.world().resource::<MemoryDom>();
+        assert!(dom.children(div).is_empty());
+        assert_eq!(dom.parent(span), None);
+        assert!(dom.get(span).is_some());
+    }
+
+    #[test]
+    fn replaced_elements_keep_children() {
+        let mut app = app();
+        let body = body(&mut app);
+
+        let text = app.world_mut().spawn(Text::new("hello")).id();
+        let div = app
+            .world_mut()
+            .spawn((ChildOf(body), Div))
+            .add_child(text)
+            .id();
+        app.update();
+        let old = node(&app, div);
+
+        app.world_mut()
+            .entity_mut(div)
+            .insert(HtmlElementName("section"));
+        app.update();
+
+        let (section, text) = (node(&app, div), node(&app, text));
+        let body = node(&app, body);
+        let dom = app.world().resource::<MemoryDom>();
+        assert!(dom.get(old).is_none());
+        assert_eq!(dom.element(section).unwrap().name, "section");
+        assert_eq!(dom.children(body), [section]);
+        assert_eq!(dom.children(section), [text]);
+    }
+
+    #[test]
+    fn remove_drops_unowned_descendants() {
+        let mut dom = MemoryDom::default();
+        let outer = dom.create_element("div").unwrap();
+        let inner = dom.create_element("span").unwrap();
+        let text = dom.create_text("hello").unwrap();
+
+        dom.append_child(&dom.document(), &outer).unwrap();
+        dom.append_child(&outer, &inner).unwrap();
+        dom.append_child(&inner, &text).unwrap();
+        dom.remove(&outer);
+
+        assert!(dom.children(dom.document()).is_empty());
+        assert!(dom.get(inner).is_none());
+        assert!(dom.get(text).is_none());
+    }
+
+    #[test]
+    fn rejects_cycles() {
+        let mut dom = MemoryDom::default();
+        let outer = dom.create_element("div").unwrap();
+        let inner = dom.create_element("div").unwrap();
+
+        dom.append_child(&outer, &inner).unwrap();
+        assert!(dom.append_child(&inner, &outer).is_err());
+    }
+}
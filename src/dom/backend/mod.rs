@@ -0,0 +1,210 @@
+//! Run the DOM systems against a document other than the browser's.
+//!
+//! Every system that mirrors entities into a document goes through a
+//! [`DomBackend`] resource. [`DomPlugin`](super::DomPlugin) installs them with
+//! [`WebDom`], the browser's document, alongside the browser-only
+//! integrations. [`DomBackendPlugin`] installs them with any other backend,
+//! like [`MemoryDom`], an in-memory document suitable for native tests.
+
+use super::{DomStartupSystems, html::elements};
+use bevy_app::prelude::*;
+use bevy_ecs::{lifecycle::HookContext, prelude::*, world::DeferredWorld};
+use core::marker::PhantomData;
+
+mod memory;
+mod web;
+
+pub use memory::{MemoryDom, MemoryElement, MemoryNode, MemoryNodeId, MemoryNodeKind};
+pub use web::{WebDom, WebNode};
+
+/// A document the DOM systems can write to.
+///
+/// Node handles are cheap to clone. Operations on a handle whose node has
+/// since been removed should fail or do nothing rather than panic.
+pub trait DomBackend: Resource + Default {
+    /// A handle to a node in this document.
+    type Node: Clone + PartialEq + Send + Sync + 'static;
+
+    /// The component holding the node that backs an entity.
+    type NodeComponent: Component;
+
+    fn node(component: &Self::NodeComponent) -> &Self::Node;
+
+    /// Back `entity` with `node`, inserting [`Self::NodeComponent`] and any
+    /// other components the backend provides for it.
+    fn insert_node(&mut self, entity: &mut EntityCommands, node: Self::Node);
+
+    /// Remove the components [`Self::insert_node`] inserted.
+    fn remove_node(entity: &mut EntityCommands);
+
+    /// The document root.
+    fn document(&self) -> Self::Node;
+
+    fn create_element(&mut self, name: &str) -> Result<Self::Node>;
+    fn create_element_ns(&mut self, namespace: &str, name: &str) -> Result<Self::Node>;
+    fn create_text(&mut self, text: &str) -> Result<Self::Node>;
+
+    /// Replace the data of a text node.
+    fn set_text(&mut self, node: &Self::Node, text: &str) -> Result;
+
+    fn parent(&self, node: &Self::Node) -> Option<Self::Node>;
+    fn first_child(&self, node: &Self::Node) -> Option<Self::Node>;
+    fn next_sibling(&self, node: &Self::Node) -> Option<Self::Node>;
+
+    /// The local name of an element, or `None` for other nodes.
+    fn local_name(&self, node: &Self::Node) -> Option<String>;
+
+    /// The data of a text node, or `None` for other nodes.
+    fn text(&self, node: &Self::Node) -> Option<String>;
+
+    /// The first element in the document whose attribute `name` is `value`.
+    fn query_attribute(&self, name: &str, value: &str) -> Option<Self::Node>;
+
+    /// The entity `node` was inserted into, if any.
+    fn entity(&self, node: &Self::Node) -> Option<Entity>;
+
+    /// Insert `child` into `parent` before `reference`, or last if there is
+    /// none, moving it if it's already attached elsewhere.
+    fn insert_before(
+        &mut self,
+        parent: &Self::Node,
+        child: &Self::Node,
+        reference: Option<&Self::Node>,
+    ) -> Result;
+
+    fn append_child(&mut self, parent: &Self::Node, child: &Self::Node) -> Result {
+        self.insert_before(parent, child, None)
+    }
+
+    /// Detach `node` from its parent, if it has one.
+    fn detach(&mut self, node: &Self::Node) -> Result;
+
+    /// Detach `node` from its parent and drop it.
+    ///
+    /// Descendants owned by an entity must survive, since that entity may
+    /// be moved under a new node.
+    fn remove(&mut self, node: &Self::Node);
+
+    fn set_attribute(&mut self, node: &Self::Node, name: &str, value: &str) -> Result;
+    fn remove_attribute(&mut self, node: &Self::Node, name: &str) -> Result;
+    fn add_class(&mut self, node: &Self::Node, class: &str) -> Result;
+    fn remove_class(&mut self, node: &Self::Node, class: &str) -> Result;
+    fn set_property(&mut self, node: &Self::Node, name: &str, value: PropValue) -> Result;
+    fn set_inner_html(&mut self, node: &Self::Node, html: &str) -> Result;
+}
+
+/// A property value, as set by the [`prop`] components.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(any(test, feature = "debug"), derive(Debug))]
+pub enum PropValue {
+    String(String),
+    Bool(bool),
+}
+
+impl From<String> for PropValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<bool> for PropValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+/// The node backing an entity in the document `B`.
+///
+/// A ready-made [`DomBackend::NodeComponent`] that removes its node from the
+/// document when replaced, like [`Node`](super::html::Node) does for
+/// [`WebDom`].
+#[derive(Component)]
+#[component(on_replace = Self::on_replace_hook)]
+pub struct BackendNode<B: DomBackend>(pub B::Node);
+
+#[cfg(feature = "debug")]
+impl<B: DomBackend> std::fmt::Debug for BackendNode<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BackendNode").finish_non_exhaustive()
+    }
+}
+
+impl<B: DomBackend> core::ops::Deref for BackendNode<B> {
+    type Target = B::Node;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<B: DomBackend> BackendNode<B> {
+    fn on_replace_hook(mut world: DeferredWorld, context: HookContext) {
+        let Some(node) = world.get::<Self>(context.entity).map(|n| n.0.clone()) else {
+            return;
+        };
+
+        if let Some(mut backend) = world.get_resource_mut::<B>() {
+            backend.remove(&node);
+        }
+    }
+}
+
+/// Mirrors ECS element hierarchies into the document `B`.
+///
+/// This sets up `Html`, `Head`, and `Body` entities backed by fresh nodes, then
+/// keeps the document in sync the same way [`DomPlugin`](super::DomPlugin)
+/// keeps the browser's. Browser-only integrations, like event handlers and
+/// the router, are not installed.
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct DomBackendPlugin<B>(PhantomData<fn() -> B>);
+
+impl<B> Default for DomBackendPlugin<B> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<B: DomBackend> Plugin for DomBackendPlugin<B> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<B>().add_systems(
+            PreStartup,
+            initialize_document::<B>.in_set(DomStartupSystems::Window),
+        );
+
+        plugin::<B>(app);
+        super::configure_sets(app);
+    }
+}
+
+/// Install the systems that mirror entities into the document `B`.
+pub(super) fn plugin<B: DomBackend>(app: &mut App) {
+    super::html::plugin::<B>(app);
+    super::attr::plugin::<B>(app);
+    super::class::plugin::<B>(app);
+    super::prop::plugin::<B>(app);
+
+    app.add_systems(
+        PostUpdate,
+        super::reparent_incremental::<B>.in_set(super::DomSystems::Reparent),
+    );
+}
+
+fn initialize_document<B: DomBackend>(mut backend: ResMut<B>, mut commands: Commands) -> Result {
+    let document = backend.document();
+    let html = backend.create_element("html")?;
+    let head = backend.create_element("head")?;
+    let body = backend.create_element("body")?;
+
+    backend.append_child(&document, &html)?;
+    backend.append_child(&html, &head)?;
+    backend.append_child(&html, &body)?;
+
+    let mut html_entity = commands.spawn(elements::Html);
+    backend.insert_node(&mut html_entity, html);
+    let html = html_entity.id();
+
+    backend.insert_node(&mut commands.spawn((ChildOf(html), elements::Head)), head);
+    backend.insert_node(&mut commands.spawn((ChildOf(html), elements::Body)), body);
+
+    Ok(())
+}
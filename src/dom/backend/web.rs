@@ -0,0 +1,267 @@
+use super::{DomBackend, PropValue};
+use crate::{
+    dom::html::{
+        Element, EventTarget, HtmlElement, HtmlInputElement, HtmlSelectElement,
+        HtmlTextAreaElement, Node, NodeEntityMap, SvgElement,
+    },
+    js_err::JsErr,
+};
+use bevy_ecs::prelude::*;
+use send_wrapper::SendWrapper;
+use wasm_bindgen::{JsCast, JsValue};
+
+/// A handle to a node in the browser's document.
+#[derive(Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct WebNode(SendWrapper<web_sys::Node>);
+
+impl WebNode {
+    pub fn new(node: web_sys::Node) -> Self {
+        Self(SendWrapper::new(node))
+    }
+
+    fn element(&self) -> Result<&web_sys::Element> {
+        self.0
+            .dyn_ref::<web_sys::Element>()
+            .ok_or_else(|| format!("expected an element, found {}", self.0.node_name()).into())
+    }
+}
+
+impl PartialEq for WebNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.is_same_node(Some(&other.0))
+    }
+}
+
+impl core::ops::Deref for WebNode {
+    type Target = web_sys::Node;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// The browser's document.
+///
+/// This is the backend [`DomPlugin`](crate::dom::DomPlugin) installs. It also
+/// maps nodes back to the entities that own them for
+/// [`NodeLookup`](crate::dom::html::NodeLookup).
+#[derive(Resource)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct WebDom {
+    document: SendWrapper<web_sys::Document>,
+    entities: NodeEntityMap,
+}
+
+impl Default for WebDom {
+    fn default() -> Self {
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .expect("browser document should be available");
+
+        Self {
+            document: SendWrapper::new(document),
+            entities: NodeEntityMap::default(),
+        }
+    }
+}
+
+impl WebDom {
+    pub(crate) fn entities(&self) -> &NodeEntityMap {
+        &self.entities
+    }
+}
+
+impl DomBackend for WebDom {
+    type Node = WebNode;
+    type NodeComponent = Node;
+
+    fn node(component: &Node) -> &WebNode {
+        &component.0
+    }
+
+    fn insert_node(&mut self, entity: &mut EntityCommands, node: WebNode) {
+        if let Some(element) = node.dyn_ref::<web_sys::Element>() {
+            entity.insert(Element::new(element.clone()));
+
+            if let Some(html) = element.dyn_ref::<web_sys::HtmlElement>() {
+                entity.insert(HtmlElement::new(html.clone()));
+            } else if let Some(svg) = element.dyn_ref::<web_sys::SvgElement>() {
+                entity.insert(SvgElement::new(svg.clone()));
+            }
+        }
+
+        entity.insert(Node(node));
+    }
+
+    fn remove_node(entity: &mut EntityCommands) {
+        entity.try_remove::<(
+            Node,
+            EventTarget,
+            HtmlElement,
+            Element,
+            SvgElement,
+            HtmlInputElement,
+            HtmlTextAreaElement,
+            HtmlSelectElement,
+        )>();
+    }
+
+    fn document(&self) -> WebNode {
+        let document: &web_sys::Node = &self.document;
+        WebNode::new(document.clone())
+    }
+
+    fn create_element(&mut self, name: &str) -> Result<WebNode> {
+        let element = self.document.create_element(name).js_err()?;
+        Ok(WebNode::new(element.unchecked_into()))
+    }
+
+    fn create_element_ns(&mut self, namespace: &str, name: &str) -> Result<WebNode> {
+        let element = self
+            .document
+            .create_element_ns(Some(namespace), name)
+            .js_err()?;
+        Ok(WebNode::new(element.unchecked_into()))
+    }
+
+    fn create_text(&mut self, text: &str) -> Result<WebNode> {
+        Ok(WebNode::new(
+            self.document.create_text_node(text).unchecked_into(),
+        ))
+    }
+
+    fn set_text(&mut self, node: &WebNode, text: &str) -> Result {
+        let text_node = node
+            .dyn_ref::<web_sys::Text>()
+            .ok_or("expected a text node")?;
+        text_node.set_data(text);
+
+        Ok(())
+    }
+
+    fn parent(&self, node: &WebNode) -> Option<WebNode> {
+        node.parent_node().map(WebNode::new)
+    }
+
+    fn first_child(&self, node: &WebNode) -> Option<WebNode> {
+        node.first_child().map(WebNode::new)
+    }
+
+    fn next_sibling(&self, node: &WebNode) -> Option<WebNode> {
+        node.next_sibling().map(WebNode::new)
+    }
+
+    fn local_name(&self, node: &WebNode) -> Option<String> {
+        node.dyn_ref::<web_sys::Element>().map(|e| e.local_name())
+    }
+
+    fn text(&self, node: &WebNode) -> Option<String> {
+        if node.node_type() == web_sys::Node::TEXT_NODE {
+            node.text_content()
+        } else {
+            None
+        }
+    }
+
+    fn query_attribute(&self, name: &str, value: &str) -> Option<WebNode> {
+        let selector = format!("[{name}=\"{}\"]", css_string(value));
+        let element = self.document.query_selector(&selector).ok()??;
+
+        Some(WebNode::new(element.unchecked_into()))
+    }
+
+    fn entity(&self, node: &WebNode) -> Option<Entity> {
+        self.entities.get(node)
+    }
+
+    fn insert_before(
+        &mut self,
+        parent: &WebNode,
+        child: &WebNode,
+        reference: Option<&WebNode>,
+    ) -> Result {
+        parent
+            .insert_before(child, reference.map(|r| &**r))
+            .js_err()?;
+
+        Ok(())
+    }
+
+    fn detach(&mut self, node: &WebNode) -> Result {
+        if let Some(parent) = node.parent_node() {
+            parent.remove_child(node).js_err()?;
+        }
+
+        Ok(())
+    }
+
+    fn remove(&mut self, node: &WebNode) {
+        // Descendants stay attached to the removed node, so an entity whose
+        // parent's element was replaced can be moved to the new one.
+        let _ = self.detach(node);
+    }
+
+    fn set_attribute(&mut self, node: &WebNode, name: &str, value: &str) -> Result {
+        node.element()?.set_attribute(name, value).js_err()
+    }
+
+    fn remove_attribute(&mut self, node: &WebNode, name: &str) -> Result {
+        node.element()?.remove_attribute(name).js_err()
+    }
+
+    fn add_class(&mut self, node: &WebNode, class: &str) -> Result {
+        node.element()?.class_list().add_1(class).js_err()
+    }
+
+    fn remove_class(&mut self, node: &WebNode, class: &str) -> Result {
+        node.element()?.class_list().remove_1(class).js_err()
+    }
+
+    fn set_property(&mut self, node: &WebNode, name: &str, value: PropValue) -> Result {
+        let value = match value {
+            PropValue::String(value) => JsValue::from(value),
+            PropValue::Bool(value) => JsValue::from(value),
+        };
+
+        js_sys::Reflect::set(node, &JsValue::from_str(name), &value).js_err()?;
+
+        Ok(())
+    }
+
+    fn set_inner_html(&mut self, node: &WebNode, html: &str) -> Result {
+        node.element()?.set_inner_html(html);
+
+        Ok(())
+    }
+}
+
+/// Escape `value` for use between double quotes in a CSS selector.
+fn css_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        match char {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(char);
+            }
+            // Line breaks and other control characters can only appear as
+            // code points.
+            c if c.is_control() => escaped.push_str(&format!("\\{:x} ", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_css_string() {
+        assert_eq!(css_string("intro"), "intro");
+        assert_eq!(css_string(r#"a"b\c"#), r#"a\"b\\c"#);
+        assert_eq!(css_string("a\nb"), "a\\a b");
+    }
+}
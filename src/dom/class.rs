@@ -1,16 +1,14 @@
-use super::{DomSystems, html::Element};
-use crate::js_err::JsErr;
+use super::{DomSystems, backend::DomBackend};
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use std::borrow::Cow;
 
-pub(super) struct ClassPlugin;
-
-impl Plugin for ClassPlugin {
-    fn build(&self, app: &mut App) {
-        app.add_systems(PostUpdate, Class::attach_class.in_set(DomSystems::Attach))
-            .add_observer(Class::observe_remove);
-    }
+pub(super) fn plugin<B: DomBackend>(app: &mut App) {
+    app.add_systems(
+        PostUpdate,
+        Class::attach_class::<B>.in_set(DomSystems::Attach),
+    )
+    .add_observer(Class::observe_remove::<B>);
 }
 
 #[derive(Component)]
@@ -63,25 +61,23 @@ impl Class {
         Self(Cow::Borrowed(class))
     }
 
-    fn attach_class(
+    fn attach_class<B: DomBackend>(
         texts: Query<(&Self, &ClassOf), Changed<Self>>,
-        element: Query<&Element>,
+        element: Query<&B::NodeComponent>,
+        mut backend: ResMut<B>,
     ) -> Result {
         for (class, parent) in &texts {
-            element
-                .get(parent.0)?
-                .class_list()
-                .add_1(&class.0)
-                .js_err()?;
+            backend.add_class(B::node(element.get(parent.0)?), &class.0)?;
         }
 
         Ok(())
     }
 
-    fn observe_remove(
+    fn observe_remove<B: DomBackend>(
         trigger: On<Replace, Self>,
         class: Query<(&Self, &ClassOf)>,
-        element: Query<&Element>,
+        element: Query<&B::NodeComponent>,
+        mut backend: ResMut<B>,
     ) -> Result {
         let Ok((class, parent)) = class.get(trigger.entity) else {
             return Ok(());
@@ -90,9 +86,7 @@ impl Class {
             return Ok(());
         };
 
-        element.class_list().remove_1(&class.0).js_err()?;
-
-        Ok(())
+        backend.remove_class(B::node(element), &class.0)
     }
 }
 
@@ -0,0 +1,243 @@
+//! Delegated handlers, dispatched from one listener on the document per event.
+
+use super::{EventHandler, Events, run_handler};
+use crate::dom::html::{Document, NodeLookup};
+use crate::js_err::JsErr;
+use bevy_app::App;
+use bevy_ecs::{prelude::*, system::SystemState};
+use bevy_platform::collections::HashMap;
+use send_wrapper::SendWrapper;
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::AddEventListenerOptions;
+
+/// The listener options delegated handlers share a document listener by.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+struct Key {
+    event: &'static str,
+    capturing: bool,
+    passive: Option<bool>,
+}
+
+impl Key {
+    fn of(handler: &EventHandler<web_sys::Event>) -> Self {
+        Self {
+            event: handler.event,
+            capturing: handler.capturing,
+            passive: handler.passive,
+        }
+    }
+}
+
+/// The document listeners added for delegated handlers. These stay for the
+/// app's lifetime, and find no handlers to run once the last one is gone.
+#[derive(Resource, Default)]
+pub(super) struct DelegatedListeners(HashMap<Key, SendWrapper<Closure<dyn FnMut(web_sys::Event)>>>);
+
+pub(super) fn manage_delegated(
+    handlers: Query<&EventHandler<web_sys::Event>, Added<EventHandler<web_sys::Event>>>,
+    document: Single<&Document>,
+    mut listeners: ResMut<DelegatedListeners>,
+) -> Result {
+    for handler in &handlers {
+        let key = Key::of(handler);
+        if !handler.delegated || listeners.0.contains_key(&key) {
+            continue;
+        }
+
+        let mut lookup = None;
+        let function = Closure::new(move |ev: web_sys::Event| {
+            let res = crate::runner::app_scope(|app| {
+                let lookup = lookup.get_or_insert_with(|| SystemState::new(app.world_mut()));
+                dispatch(app, lookup, key, ev);
+            });
+
+            if res.is_err() {
+                log::error!("Failed to borrow app for delegated event handler");
+            }
+        });
+
+        let options = AddEventListenerOptions::new();
+        options.set_capture(key.capturing);
+        if let Some(passive) = key.passive {
+            options.set_passive(passive);
+        }
+
+        document
+            .add_event_listener_with_callback_and_add_event_listener_options(
+                key.event,
+                function.as_ref().unchecked_ref(),
+                &options,
+            )
+            .js_err()?;
+
+        listeners.0.insert(key, SendWrapper::new(function));
+    }
+
+    Ok(())
+}
+
+/// Run the delegated handlers for `ev` along its path through the entity
+/// hierarchy.
+fn dispatch(
+    app: &mut App,
+    lookup: &mut SystemState<NodeLookup<'static>>,
+    key: Key,
+    ev: web_sys::Event,
+) {
+    let world = app.world();
+    let Some(target) = lookup.get(world).event_target(&ev) else {
+        return;
+    };
+
+    propagate(app, target, key, |app, handler| {
+        run_handler(app, handler, ev.clone());
+        ev.cancel_bubble()
+    });
+}
+
+/// Call `run` with each delegated handler along the path from `target`. It
+/// returns whether propagation was stopped, which ends the dispatch once the
+/// current entity's handlers have run, as in the browser.
+fn propagate(
+    app: &mut App,
+    target: Entity,
+    key: Key,
+    mut run: impl FnMut(&mut App, Entity) -> bool,
+) {
+    for entity in propagation_path(app.world(), target, key.capturing) {
+        let mut stopped = false;
+        for handler in handlers_for(app.world(), entity, key) {
+            stopped |= run(app, handler);
+        }
+
+        if stopped {
+            break;
+        }
+    }
+}
+
+/// The entities an event targeting `target` passes through: up from the
+/// target while bubbling, or down to it while capturing.
+fn propagation_path(world: &World, target: Entity, capturing: bool) -> Vec<Entity> {
+    let mut path = vec![target];
+    let mut current = target;
+    while let Some(parent) = world.get::<ChildOf>(current).map(ChildOf::parent) {
+        path.push(parent);
+        current = parent;
+    }
+
+    if capturing {
+        path.reverse();
+    }
+
+    path
+}
+
+/// The delegated handlers of `entity` matching `key`, in the order they were
+/// added.
+fn handlers_for(world: &World, entity: Entity, key: Key) -> Vec<Entity> {
+    world
+        .get::<Events>(entity)
+        .into_iter()
+        .flat_map(|events| events.iter())
+        .filter(|handler| {
+            world
+                .get::<EventHandler<web_sys::Event>>(*handler)
+                .is_some_and(|handler| handler.delegated && Key::of(handler) == key)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dom::events::{Bevent, Ev, EventOf, ev};
+
+    #[test]
+    fn test_delegated_order() {
+        let mut world = World::new();
+        let world = &mut world;
+
+        let outer = world.spawn_empty().id();
+        let inner = world.spawn(ChildOf(outer)).id();
+        let target = world.spawn(ChildOf(inner)).id();
+
+        let delegated = |world: &mut World, element| {
+            world
+                .spawn((EventOf(element), ev::click(|_: Ev<_>| {}).delegated()))
+                .id()
+        };
+        let on_outer = delegated(world, outer);
+        let on_target = delegated(world, target);
+        world.spawn((EventOf(inner), ev::click(|_: Ev<_>| {})));
+        world.spawn((EventOf(inner), ev::key_down(|_: Ev<_>| {}).delegated()));
+
+        world.run_system_cached(Bevent::transform).unwrap();
+
+        assert_eq!(
+            propagation_path(world, target, false),
+            [target, inner, outer]
+        );
+        assert_eq!(
+            propagation_path(world, target, true),
+            [outer, inner, target]
+        );
+
+        let key = Key {
+            event: "click",
+            capturing: false,
+            passive: None,
+        };
+        assert_eq!(handlers_for(world, target, key), [on_target]);
+        assert_eq!(handlers_for(world, inner, key), []);
+        assert_eq!(handlers_for(world, outer, key), [on_outer]);
+    }
+
+    #[test]
+    fn test_dispatch_stops_propagation() {
+        let mut app = App::new();
+        let world = app.world_mut();
+
+        let outer = world.spawn_empty().id();
+        let inner = world.spawn(ChildOf(outer)).id();
+        let target = world.spawn(ChildOf(inner)).id();
+
+        let delegated = |world: &mut World, element| {
+            world
+                .spawn((EventOf(element), ev::click(|_: Ev<_>| {}).delegated()))
+                .id()
+        };
+        let on_outer = delegated(world, outer);
+        let on_inner = delegated(world, inner);
+        let stops = delegated(world, inner);
+        let on_target = delegated(world, target);
+
+        world.run_system_cached(Bevent::transform).unwrap();
+
+        let dispatch = |app: &mut App, stop_at| {
+            let key = Key {
+                event: "click",
+                capturing: false,
+                passive: None,
+            };
+            let mut ran = Vec::new();
+            propagate(app, target, key, |_, handler| {
+                ran.push(handler);
+                Some(handler) == stop_at
+            });
+            ran
+        };
+
+        assert_eq!(
+            dispatch(&mut app, None),
+            [on_target, on_inner, stops, on_outer]
+        );
+        // The rest of the entity's handlers still run.
+        assert_eq!(
+            dispatch(&mut app, Some(on_inner)),
+            [on_target, on_inner, stops]
+        );
+        assert_eq!(dispatch(&mut app, Some(on_target)), [on_target]);
+    }
+}
@@ -6,17 +6,26 @@ use super::Ev;
 #[cfg_attr(feature = "debug", derive(Debug))]
 pub struct Concise;
 
-/// A marker type for fallible handlers.
-#[cfg_attr(feature = "debug", derive(Debug))]
-pub struct Fallible;
-
 /// Conversion trait to turn a function or closure into an
 /// event-handling system.
+///
+/// Handlers may take the event or nothing at all, and may return `()` or
+/// `Result`. Errors are reported to the app's error handler.
+///
+/// ```ignore
+/// ev::click(|ev: Ev<PointerEvent>, mut commands: Commands| -> Result {
+///     commands.entity(ev.target()).despawn();
+///     Ok(())
+/// })
+/// ```
 pub trait IntoHandlerSystem<E, M> {
     /// Convert `Self` into an event-handling system.
     fn into_handler(self) -> impl System<In = Ev<E>, Out = Result>;
 }
 
+// Systems returning `Result` also output `()` through Bevy's `IntoResult`,
+// surfacing errors as `RunSystemError::Failed`. Separate impls for them would
+// be ambiguous.
 impl<S, E, M> IntoHandlerSystem<E, (M,)> for S
 where
     S: IntoSystem<Ev<E>, (), M>,
@@ -39,24 +48,39 @@ where
     }
 }
 
-// impl<S, E, M> IntoHandlerSystem<E, (M, Fallible)> for S
-// where
-//     S: IntoSystem<Ev<E>, Result, M>,
-//     E: 'static,
-// {
-//     fn into_handler(self) -> impl System<In = Ev<E>, Out = Result> {
-//         IntoSystem::into_system(self)
-//     }
-// }
-//
-// impl<S, E, M> IntoHandlerSystem<E, (M, Fallible, Concise)> for S
-// where
-//     S: IntoSystem<(), Result, M>,
-//     E: 'static,
-// {
-//     fn into_handler(self) -> impl System<In = Ev<E>, Out = Result> {
-//         let input = |_: Ev<E>| ();
-//
-//         IntoSystem::into_system(input.pipe(self))
-//     }
-// }
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dom::events::JsEvent;
+    use bevy_ecs::system::RegisteredSystemError;
+    use send_wrapper::SendWrapper;
+
+    fn fails<M>(handler: impl IntoHandlerSystem<u32, M>) -> bool {
+        let mut world = World::new();
+        let id = world.register_system(handler.into_handler());
+        let event = JsEvent {
+            entity: Entity::PLACEHOLDER,
+            event: SendWrapper::new(1),
+        };
+
+        matches!(
+            world.run_system_with(id, event),
+            Ok(Err(_)) | Err(RegisteredSystemError::Failed(_))
+        )
+    }
+
+    #[test]
+    fn test_fallible_handlers() {
+        assert!(!fails(|_: Ev<u32>| {}));
+        assert!(!fails(|| {}));
+        assert!(!fails(|ev: Ev<u32>| -> Result {
+            (*ev.0 == 1).then_some(()).ok_or("unexpected event".into())
+        }));
+
+        assert!(fails(|_: Ev<u32>| -> Result { Err("failed".into()) }));
+        assert!(fails(|| -> Result { Err("failed".into()) }));
+        assert!(fails(|_: Ev<u32>, _: &mut World| -> Result {
+            Err("failed".into())
+        }));
+    }
+}
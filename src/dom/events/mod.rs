@@ -12,11 +12,12 @@ use bevy_query_observer::{AddStopObserver, Stop};
 use bevy_utils::prelude::DebugName;
 use core::ops::Deref;
 use send_wrapper::SendWrapper;
-use std::{any::TypeId, collections::HashSet};
+use std::{any::TypeId, collections::HashSet, time::Duration};
 use wasm_bindgen::{JsCast, convert::FromWasmAbi, prelude::Closure};
 use web_sys::AddEventListenerOptions;
 
 mod defer;
+mod delegate;
 mod handler;
 mod params;
 
@@ -45,6 +46,9 @@ pub struct Bevent {
     trigger: bool,
     capturing: bool,
     passive: Option<bool>,
+    delegated: bool,
+    once: bool,
+    limit: Option<RateLimit>,
 }
 
 #[cfg(feature = "debug")]
@@ -54,6 +58,9 @@ impl std::fmt::Debug for Bevent {
             .field("event", &self.event)
             .field("trigger", &self.trigger)
             .field("capturing", &self.capturing)
+            .field("delegated", &self.delegated)
+            .field("once", &self.once)
+            .field("limit", &self.limit)
             .finish_non_exhaustive()
     }
 }
@@ -87,6 +94,9 @@ impl Bevent {
             trigger: true,
             capturing: false,
             passive: None,
+            delegated: false,
+            once: false,
+            limit: None,
         }
     }
 
@@ -117,6 +127,58 @@ impl Bevent {
         }
     }
 
+    /// Share a single listener on the document with every other delegated
+    /// handler of this event, instead of adding one to the element.
+    ///
+    /// This suits handlers repeated across many elements, like the rows of a
+    /// large list. Handlers run in bubbling order, from the target's entity up
+    /// through its ancestors, or the reverse while [capturing](Self::capturing).
+    /// Calling `stop_propagation` stops at the current entity.
+    ///
+    /// Events that don't bubble, like `focus` or `pointerenter`, only reach
+    /// the document while capturing. Prefer `focusin` or `pointerover` there.
+    #[inline(always)]
+    pub fn delegated(self) -> Self {
+        Self {
+            delegated: true,
+            ..self
+        }
+    }
+
+    /// Despawn this handler after its system first runs.
+    #[inline(always)]
+    pub fn once(self) -> Self {
+        Self { once: true, ..self }
+    }
+
+    /// Run the system at most once per `period`, dropping the events in
+    /// between.
+    ///
+    /// Dropped events don't trigger an ECS update either.
+    #[inline(always)]
+    pub fn throttle(self, period: Duration) -> Self {
+        Self {
+            limit: Some(RateLimit::Throttle {
+                period,
+                waiting: false,
+            }),
+            ..self
+        }
+    }
+
+    /// Run the system once no event has arrived for `delay`, with the last
+    /// event.
+    ///
+    /// The browser has dispatched the event by then, so calling
+    /// `prevent_default` or `stop_propagation` has no effect.
+    #[inline(always)]
+    pub fn debounce(self, delay: Duration) -> Self {
+        Self {
+            limit: Some(RateLimit::Debounce { delay, pending: 0 }),
+            ..self
+        }
+    }
+
     fn transform(world: &mut World) {
         let mut clicks =
             world.query_filtered::<Entity, (With<Self>, Without<EventHandler<web_sys::Event>>)>();
@@ -131,6 +193,9 @@ impl Bevent {
             let capturing = ev.capturing;
             let event = ev.event;
             let passive = ev.passive;
+            let delegated = ev.delegated;
+            let once = ev.once;
+            let limit = ev.limit;
 
             let (id, name) = handler(world);
             world.entity_mut(click).insert(EventHandler {
@@ -141,6 +206,9 @@ impl Bevent {
                 trigger,
                 capturing,
                 passive,
+                delegated,
+                once,
+                limit,
             });
         }
     }
@@ -154,11 +222,16 @@ impl Bevent {
             .0
             .insert(core::any::TypeId::of::<web_sys::Event>())
         {
-            app.add_systems(
-                PostUpdate,
-                manage_handlers::<web_sys::Event>.after(DomSystems::Attach),
-            )
-            .add_stop_observer(EventHandler::<web_sys::Event>::stop_event);
+            app.init_resource::<delegate::DelegatedListeners>()
+                .add_systems(
+                    PostUpdate,
+                    (
+                        manage_handlers::<web_sys::Event>,
+                        delegate::manage_delegated,
+                    )
+                        .after(DomSystems::Attach),
+                )
+                .add_stop_observer(EventHandler::<web_sys::Event>::stop_event);
         }
     }
 }
@@ -247,6 +320,9 @@ macro_rules! handler {
 }
 
 pub mod ev {
+    use super::{Bevent, IntoHandlerSystem};
+    use wasm_bindgen::JsCast;
+
     handler! { click, "click", web_sys::PointerEvent }
     handler! { submit, "submit", web_sys::SubmitEvent }
     handler! { pointer_down, "pointerdown", web_sys::PointerEvent }
@@ -256,6 +332,8 @@ pub mod ev {
     handler! { pointer_leave, "pointerleave", web_sys::PointerEvent }
     handler! { context_menu, "contextmenu", web_sys::PointerEvent }
     handler! { pop_state, "popstate", web_sys::PopStateEvent }
+    handler! { hash_change, "hashchange", web_sys::HashChangeEvent }
+    handler! { before_unload, "beforeunload", web_sys::BeforeUnloadEvent }
     handler! { select_start, "selectstart", web_sys::Event }
     handler! { key_down, "keydown", web_sys::KeyboardEvent }
     handler! { key_up, "keyup", web_sys::KeyboardEvent }
@@ -266,6 +344,102 @@ pub mod ev {
     handler! { wheel, "wheel", web_sys::WheelEvent }
     handler! { load, "load", web_sys::Event }
     handler! { error, "error", web_sys::Event }
+
+    handler! { dbl_click, "dblclick", web_sys::MouseEvent }
+    handler! { reset, "reset", web_sys::Event }
+    handler! { invalid, "invalid", web_sys::Event }
+    handler! { toggle, "toggle", web_sys::ToggleEvent }
+
+    handler! { pointer_cancel, "pointercancel", web_sys::PointerEvent }
+    handler! { pointer_over, "pointerover", web_sys::PointerEvent }
+    handler! { pointer_out, "pointerout", web_sys::PointerEvent }
+    handler! { got_pointer_capture, "gotpointercapture", web_sys::PointerEvent }
+    handler! { lost_pointer_capture, "lostpointercapture", web_sys::PointerEvent }
+
+    handler! { touch_start, "touchstart", web_sys::TouchEvent }
+    handler! { touch_move, "touchmove", web_sys::TouchEvent }
+    handler! { touch_end, "touchend", web_sys::TouchEvent }
+    handler! { touch_cancel, "touchcancel", web_sys::TouchEvent }
+
+    handler! { drag_start, "dragstart", web_sys::DragEvent }
+    handler! { drag, "drag", web_sys::DragEvent }
+    handler! { drag_end, "dragend", web_sys::DragEvent }
+    handler! { drag_enter, "dragenter", web_sys::DragEvent }
+    handler! { drag_over, "dragover", web_sys::DragEvent }
+    handler! { drag_leave, "dragleave", web_sys::DragEvent }
+    handler! { drop, "drop", web_sys::DragEvent }
+
+    handler! { scroll, "scroll", web_sys::Event }
+    handler! { scroll_end, "scrollend", web_sys::Event }
+    handler! { resize, "resize", web_sys::UiEvent }
+
+    handler! { key_press, "keypress", web_sys::KeyboardEvent }
+
+    handler! { focus_in, "focusin", web_sys::FocusEvent }
+    handler! { focus_out, "focusout", web_sys::FocusEvent }
+
+    handler! { before_input, "beforeinput", web_sys::InputEvent }
+    handler! { composition_start, "compositionstart", web_sys::CompositionEvent }
+    handler! { composition_update, "compositionupdate", web_sys::CompositionEvent }
+    handler! { composition_end, "compositionend", web_sys::CompositionEvent }
+
+    handler! { copy, "copy", web_sys::ClipboardEvent }
+    handler! { cut, "cut", web_sys::ClipboardEvent }
+    handler! { paste, "paste", web_sys::ClipboardEvent }
+
+    handler! { animation_start, "animationstart", web_sys::AnimationEvent }
+    handler! { animation_iteration, "animationiteration", web_sys::AnimationEvent }
+    handler! { animation_end, "animationend", web_sys::AnimationEvent }
+    handler! { animation_cancel, "animationcancel", web_sys::AnimationEvent }
+    handler! { transition_run, "transitionrun", web_sys::TransitionEvent }
+    handler! { transition_start, "transitionstart", web_sys::TransitionEvent }
+    handler! { transition_end, "transitionend", web_sys::TransitionEvent }
+    handler! { transition_cancel, "transitioncancel", web_sys::TransitionEvent }
+
+    handler! { play, "play", web_sys::Event }
+    handler! { playing, "playing", web_sys::Event }
+    handler! { pause, "pause", web_sys::Event }
+    handler! { ended, "ended", web_sys::Event }
+    handler! { waiting, "waiting", web_sys::Event }
+    handler! { seeking, "seeking", web_sys::Event }
+    handler! { seeked, "seeked", web_sys::Event }
+    handler! { stalled, "stalled", web_sys::Event }
+    handler! { suspend, "suspend", web_sys::Event }
+    handler! { emptied, "emptied", web_sys::Event }
+    handler! { abort, "abort", web_sys::Event }
+    handler! { can_play, "canplay", web_sys::Event }
+    handler! { can_play_through, "canplaythrough", web_sys::Event }
+    handler! { loaded_data, "loadeddata", web_sys::Event }
+    handler! { loaded_metadata, "loadedmetadata", web_sys::Event }
+    handler! { time_update, "timeupdate", web_sys::Event }
+    handler! { duration_change, "durationchange", web_sys::Event }
+    handler! { volume_change, "volumechange", web_sys::Event }
+    handler! { rate_change, "ratechange", web_sys::Event }
+    handler! { progress, "progress", web_sys::Event }
+
+    /// Handle an event this module doesn't list, like a `CustomEvent`
+    /// dispatched by a web component.
+    ///
+    /// ```ignore
+    /// ev::custom("color-picked", |ev: Ev<web_sys::CustomEvent>| {
+    ///     log::info!("{:?}", ev.detail());
+    /// })
+    /// ```
+    ///
+    /// Handlers that don't take the event name its type up front:
+    ///
+    /// ```ignore
+    /// ev::custom::<web_sys::CustomEvent, _>("color-picked", || log::info!("picked"))
+    /// ```
+    pub fn custom<E, M>(
+        name: &'static str,
+        system: impl IntoHandlerSystem<E, M> + Send + Sync + 'static,
+    ) -> Bevent
+    where
+        E: JsCast + 'static,
+    {
+        Bevent::new(name, system)
+    }
 }
 
 #[derive(Component)]
@@ -280,6 +454,27 @@ pub struct EventHandler<E: FromWasmAbi + 'static> {
     trigger: bool,
     capturing: bool,
     passive: Option<bool>,
+    /// Dispatched from a listener on the document.
+    delegated: bool,
+    /// Despawn after the first run.
+    once: bool,
+    limit: Option<RateLimit>,
+}
+
+/// How often a handler's system may run, with the state of its timer.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+enum RateLimit {
+    Throttle {
+        period: Duration,
+        /// Set until `period` has passed since the last run.
+        waiting: bool,
+    },
+    Debounce {
+        delay: Duration,
+        /// Counts events, so a timer can tell whether another came after it.
+        pending: u32,
+    },
 }
 
 impl<E: FromWasmAbi + 'static> EventHandler<E> {
@@ -322,6 +517,10 @@ where
     E: FromWasmAbi + 'static,
 {
     for (entity, mut handler, node_entity) in &mut handlers {
+        if handler.delegated {
+            continue;
+        }
+
         let node = nodes.get(node_entity.0)?;
 
         let options = AddEventListenerOptions::new();
@@ -341,55 +540,9 @@ where
                 .js_err()?;
             }
             None => {
-                let event_name = handler.event;
-                let id = handler.handler;
-                let trigger = handler.trigger;
-                let name = handler.name.clone();
                 let function = Closure::new(move |ev: E| {
                     let res = crate::runner::app_scope(|app| {
-                        let world = app.world_mut();
-
-                        if trigger {
-                            match event_name {
-                                "pointerdown" | "mousedown" | "keydown" => {
-                                    // prefer synchronous execution for paired events
-                                    world.resource_mut::<ScheduleTrigger>().trigger();
-                                }
-                                _ => {
-                                    world.resource_mut::<ScheduleTrigger>().trigger_async();
-                                }
-                            }
-                        }
-
-                        let result = world.run_system_with(
-                            id,
-                            JsEvent {
-                                entity,
-                                event: SendWrapper::new(ev),
-                            },
-                        );
-
-                        match result {
-                            Ok(Err(e)) | Err(RegisteredSystemError::Failed(e)) => {
-                                let tick = world.change_tick();
-                                match app.get_error_handler() {
-                                    Some(error_handler) => error_handler(
-                                        e,
-                                        ErrorContext::System {
-                                            name: name.clone(),
-                                            last_run: tick,
-                                        },
-                                    ),
-                                    None => {
-                                        log::error!("Failed to execute event handler: {e:?}");
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                log::error!("Failed to execute event handler: {e:?}");
-                            }
-                            Ok(Ok(())) => {}
-                        }
+                        run_handler(app, entity, ev);
                     });
 
                     if res.is_err() {
@@ -411,3 +564,170 @@ where
 
     Ok(())
 }
+
+/// Run the handler `entity` for a browser event, unless its rate limit holds
+/// the event back.
+fn run_handler<E: FromWasmAbi + 'static>(app: &mut App, entity: Entity, ev: E) {
+    let Some(mut handler) = app.world_mut().get_mut::<EventHandler<E>>(entity) else {
+        return;
+    };
+
+    // The timer state isn't a change `manage_handlers` should see.
+    match &mut handler.bypass_change_detection().limit {
+        None => {}
+        Some(RateLimit::Throttle { period, waiting }) => {
+            if *waiting {
+                return;
+            }
+
+            *waiting = true;
+            crate::time::timeout(*period, move |app| {
+                if let Some(mut handler) = app.world_mut().get_mut::<EventHandler<E>>(entity)
+                    && let Some(RateLimit::Throttle { waiting, .. }) =
+                        &mut handler.bypass_change_detection().limit
+                {
+                    *waiting = false;
+                }
+            });
+        }
+        Some(RateLimit::Debounce { delay, pending }) => {
+            *pending = pending.wrapping_add(1);
+            let event = *pending;
+
+            crate::time::timeout(*delay, move |app| {
+                let latest = app
+                    .world()
+                    .get::<EventHandler<E>>(entity)
+                    .and_then(|handler| match handler.limit {
+                        Some(RateLimit::Debounce { pending, .. }) => Some(pending),
+                        _ => None,
+                    });
+
+                if latest == Some(event) {
+                    run_handler_system(app, entity, ev);
+                }
+            });
+
+            return;
+        }
+    }
+
+    run_handler_system(app, entity, ev);
+}
+
+/// Run the system of the handler `entity`, reporting its errors to the app's
+/// error handler.
+fn run_handler_system<E: FromWasmAbi + 'static>(app: &mut App, entity: Entity, ev: E) {
+    let world = app.world_mut();
+    let Some(handler) = world.get::<EventHandler<E>>(entity) else {
+        return;
+    };
+    let id = handler.handler;
+    let name = handler.name.clone();
+    let event = handler.event;
+    let trigger = handler.trigger;
+    let once = handler.once;
+
+    if trigger {
+        match event {
+            "pointerdown" | "mousedown" | "touchstart" | "keydown" => {
+                // prefer synchronous execution for paired events
+                world.resource_mut::<ScheduleTrigger>().trigger();
+            }
+            _ => {
+                world.resource_mut::<ScheduleTrigger>().trigger_async();
+            }
+        }
+    }
+
+    let result = world.run_system_with(
+        id,
+        JsEvent {
+            entity,
+            event: SendWrapper::new(ev),
+        },
+    );
+
+    match result {
+        Ok(Err(e)) | Err(RegisteredSystemError::Failed(e)) => {
+            let tick = world.change_tick();
+            match app.get_error_handler() {
+                Some(error_handler) => error_handler(
+                    e,
+                    ErrorContext::System {
+                        name,
+                        last_run: tick,
+                    },
+                ),
+                None => {
+                    log::error!("Failed to execute event handler: {e:?}");
+                }
+            }
+        }
+        Err(e) => {
+            log::error!("Failed to execute event handler: {e:?}");
+        }
+        Ok(Ok(())) => {}
+    }
+
+    // The system may have despawned it already.
+    if once && let Ok(handler) = app.world_mut().get_entity_mut(entity) {
+        handler.despawn();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Resource)]
+    struct LastEvent(i32);
+
+    #[test]
+    fn test_event_names() {
+        assert_eq!(ev::context_menu(|| {}).event, "contextmenu");
+        assert_eq!(ev::select_start(|| {}).event, "selectstart");
+        assert_eq!(ev::dbl_click(|| {}).event, "dblclick");
+
+        let typed = ev::custom::<web_sys::CustomEvent, _>("color-picked", || {});
+        assert_eq!(typed.event, "color-picked");
+        let inferred = ev::custom("color-picked", |_: Ev<web_sys::CustomEvent>| {});
+        assert_eq!(inferred.event, "color-picked");
+    }
+
+    #[test]
+    fn test_once() {
+        let mut app = App::new();
+        let world = app.world_mut();
+
+        let handler = world.register_system(|ev: In<JsEvent<i32>>, mut commands: Commands| {
+            commands.insert_resource(LastEvent(*ev.event));
+            Ok(())
+        });
+        let handler = |world: &mut World, once| {
+            world
+                .spawn(EventHandler {
+                    handler,
+                    name: DebugName::borrowed("test"),
+                    event: "click",
+                    closure: None,
+                    trigger: false,
+                    capturing: false,
+                    passive: None,
+                    delegated: false,
+                    once,
+                    limit: None,
+                })
+                .id()
+        };
+        let kept = handler(world, false);
+        let once = handler(world, true);
+
+        run_handler(&mut app, kept, 1);
+        assert!(app.world().get_entity(kept).is_ok());
+
+        run_handler(&mut app, once, 2);
+        assert!(app.world().get_entity(once).is_err());
+        assert_eq!(app.world().resource::<LastEvent>().0, 2);
+    }
+}
@@ -0,0 +1,444 @@
+use super::{HtmlElementName, Text, elements, svg::SvgElementName};
+use crate::dom::backend::DomBackend;
+use bevy_ecs::{prelude::*, system::SystemParam};
+use std::borrow::Cow;
+
+/// The attribute server-rendered elements carry their [`HydrationId`] in.
+pub const HYDRATION_ID_ATTRIBUTE: &str = "data-bweb-hid";
+
+/// While present, entities spawned without a node adopt the matching
+/// server-rendered node already in the document instead of creating one.
+///
+/// Nodes are matched by their position under the parent's node or, for
+/// entities with a [`HydrationId`], by id. Hydration runs once, during the
+/// first update after this resource is inserted, and then removes it.
+#[derive(Resource, Default)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct Hydrate;
+
+/// Match this entity to the server-rendered element with the same id, wherever
+/// it is in the document, rather than by position.
+#[derive(Component, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+#[cfg_attr(feature = "reflect", reflect(Component))]
+pub struct HydrationId(pub Cow<'static, str>);
+
+impl HydrationId {
+    pub fn new(id: impl Into<Cow<'static, str>>) -> Self {
+        Self(id.into())
+    }
+}
+
+type HydrationData<B> = (
+    Option<&'static <B as DomBackend>::NodeComponent>,
+    Option<&'static Children>,
+    Option<&'static HtmlElementName>,
+    Option<&'static SvgElementName>,
+    Option<&'static Text>,
+    Option<&'static HydrationId>,
+);
+
+#[derive(SystemParam)]
+pub(super) struct Hydrator<'w, 's, B: DomBackend> {
+    entities: Query<'w, 's, HydrationData<B>>,
+    dom: ResMut<'w, B>,
+    commands: Commands<'w, 's>,
+}
+
+pub(super) fn hydrate<B: DomBackend>(
+    root: Single<(Entity, &B::NodeComponent), With<elements::Html>>,
+    mut hydrator: Hydrator<B>,
+) -> Result {
+    hydrator.commands.remove_resource::<Hydrate>();
+
+    let (root, root_node) = root.into_inner();
+    let mut mismatches = Vec::new();
+    hydrator.children(root, &B::node(root_node).clone(), &mut mismatches);
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("hydration mismatch: {}", mismatches.join("; ")).into())
+    }
+}
+
+/// Where hydration is in a parent node's children.
+struct Cursor<N> {
+    next: Option<N>,
+    adopted: Vec<N>,
+}
+
+impl<B: DomBackend> Hydrator<'_, '_, B> {
+    /// Adopt nodes for `parent`'s children, in order, from the children of
+    /// `parent_node`.
+    fn children(&mut self, parent: Entity, parent_node: &B::Node, mismatches: &mut Vec<String>) {
+        let mut cursor = Cursor {
+            next: self.dom.first_child(parent_node),
+            adopted: Vec::new(),
+        };
+
+        self.adopt_children(parent, &mut cursor, mismatches);
+    }
+
+    /// Adopt nodes for `parent`'s children from `cursor` onwards. Returns
+    /// `false` if a mismatch ended hydration under the cursor's parent node.
+    fn adopt_children(
+        &mut self,
+        parent: Entity,
+        cursor: &mut Cursor<B::Node>,
+        mismatches: &mut Vec<String>,
+    ) -> bool {
+        let Ok((_, Some(children), ..)) = self.entities.get(parent) else {
+            return true;
+        };
+        let children: Vec<Entity> = children.iter().collect();
+
+        for child in children {
+            let Ok((node, _, html_name, svg_name, text, id)) = self.entities.get(child) else {
+                continue;
+            };
+            let id = id.map(|id| id.0.clone());
+
+            // Already backed, like the document's head and body.
+            if let Some(node) = node {
+                let node = B::node(node).clone();
+                if cursor.next.as_ref() == Some(&node) {
+                    cursor.next = self.dom.next_sibling(&node);
+                }
+                self.children(child, &node, mismatches);
+                continue;
+            }
+
+            let expected = match (html_name, svg_name, text) {
+                (Some(name), ..) => Expected::Html(name.0),
+                (_, Some(name), _) => Expected::Svg(name.0),
+                (.., Some(text)) => Expected::Text(text.to_string()),
+                // Entities without an element, like route containers, render
+                // their children in place of their own markup.
+                _ => {
+                    if !self.adopt_children(child, cursor, mismatches) {
+                        return false;
+                    }
+                    continue;
+                }
+            };
+
+            let candidate = match &id {
+                Some(id) => self.dom.query_attribute(HYDRATION_ID_ATTRIBUTE, id),
+                None => self.next_candidate(&mut cursor.next, &expected),
+            };
+
+            let matched = candidate
+                .as_ref()
+                .filter(|c| expected.matches(&*self.dom, c))
+                .cloned();
+            let Some(existing) = matched else {
+                mismatches.push(format!(
+                    "expected {expected} for {child} under {parent}, found {}",
+                    self.describe(candidate.as_ref())
+                ));
+
+                // Everything from here on is rebuilt, so drop the server
+                // markup it would otherwise be duplicated next to.
+                let mut stale = match id {
+                    Some(_) => cursor.next.take(),
+                    None => candidate.or(cursor.next.take()),
+                };
+                while let Some(node) = stale {
+                    stale = self.dom.next_sibling(&node);
+                    if self.dom.entity(&node).is_none() && !cursor.adopted.contains(&node) {
+                        self.dom.remove(&node);
+                    }
+                }
+
+                return false;
+            };
+
+            if id.is_some() && cursor.next.as_ref() == Some(&existing) {
+                cursor.next = self.dom.next_sibling(&existing);
+            }
+
+            // Differing text is kept, with the client's content.
+            if let Expected::Text(text) = &expected
+                && self.dom.text(&existing).as_deref() != Some(text.as_str())
+            {
+                mismatches.push(format!(
+                    "expected {expected} for {child} under {parent}, found {}",
+                    self.describe(Some(&existing))
+                ));
+                if let Err(e) = self.dom.set_text(&existing, text) {
+                    mismatches.push(e.to_string());
+                }
+            }
+
+            self.dom
+                .insert_node(&mut self.commands.entity(child), existing.clone());
+            cursor.adopted.push(existing.clone());
+            self.children(child, &existing, mismatches);
+        }
+
+        true
+    }
+
+    /// Take the next node at `cursor` that could back an entity. Comments,
+    /// which separate adjacent text nodes in server output, are skipped, as is
+    /// formatting whitespace when an element is expected.
+    fn next_candidate(&self, cursor: &mut Option<B::Node>, expected: &Expected) -> Option<B::Node> {
+        while let Some(node) = cursor.take() {
+            *cursor = self.dom.next_sibling(&node);
+
+            match self.dom.text(&node) {
+                Some(text) if !matches!(expected, Expected::Text(_)) && text.trim().is_empty() => {
+                    continue;
+                }
+                None if self.dom.local_name(&node).is_none() => continue,
+                _ => return Some(node),
+            }
+        }
+
+        None
+    }
+
+    fn describe(&self, node: Option<&B::Node>) -> String {
+        let Some(node) = node else {
+            return "nothing".into();
+        };
+
+        match (self.dom.local_name(node), self.dom.text(node)) {
+            (Some(name), _) => format!("<{name}>"),
+            (_, Some(text)) => format!("text {text:?}"),
+            _ => "another node".into(),
+        }
+    }
+}
+
+enum Expected {
+    Html(&'static str),
+    Svg(&'static str),
+    Text(String),
+}
+
+impl Expected {
+    fn matches<B: DomBackend>(&self, dom: &B, node: &B::Node) -> bool {
+        match self {
+            Self::Html(name) => dom
+                .local_name(node)
+                .is_some_and(|n| n.eq_ignore_ascii_case(name)),
+            Self::Svg(name) => dom.local_name(node).is_some_and(|n| n == *name),
+            Self::Text(_) => dom.text(node).is_some(),
+        }
+    }
+}
+
+impl core::fmt::Display for Expected {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Html(name) | Self::Svg(name) => write!(f, "<{name}>"),
+            Self::Text(text) => write!(f, "text {text:?}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dom::{
+        backend::{DomBackendPlugin, MemoryDom, MemoryNode, MemoryNodeId},
+        prelude::*,
+    };
+    use bevy_app::prelude::*;
+    use bevy_ecs::error::{BevyError, ErrorContext};
+    use std::cell::RefCell;
+
+    thread_local! {
+        static ERRORS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record(error: BevyError, _: ErrorContext) {
+        ERRORS.with_borrow_mut(|errors| errors.push(error.to_string()));
+    }
+
+    /// An app whose body already holds the server markup `build` creates.
+    fn app(build: impl FnOnce(&mut MemoryDom, MemoryNodeId)) -> (App, Entity) {
+        let mut app = App::new();
+        app.set_error_handler(record)
+            .add_plugins(DomBackendPlugin::<MemoryDom>::default());
+        app.update();
+
+        let body = app
+            .world_mut()
+            .query_filtered::<Entity, With<Body>>()
+            .single(app.world())
+            .unwrap();
+        let body_node = node(&app, body);
+        build(&mut app.world_mut().resource_mut::<MemoryDom>(), body_node);
+
+        app.insert_resource(Hydrate);
+        (app, body)
+    }
+
+    fn node(app: &App, entity: Entity) -> MemoryNodeId {
+        app.world().get::<MemoryNode>(entity).unwrap().0
+    }
+
+    fn element(dom: &mut MemoryDom, parent: MemoryNodeId, name: &str) -> MemoryNodeId {
+        let element = dom.create_element(name).unwrap();
+        dom.append_child(&parent, &element).unwrap();
+        element
+    }
+
+    fn text(dom: &mut MemoryDom, parent: MemoryNodeId, data: &str) -> MemoryNodeId {
+        let text = dom.create_text(data).unwrap();
+        dom.append_child(&parent, &text).unwrap();
+        text
+    }
+
+    fn errors() -> Vec<String> {
+        ERRORS.with_borrow_mut(core::mem::take)
+    }
+
+    #[test]
+    fn adopts_by_position() {
+        let mut server = None;
+        let (mut app, body) = app(|dom, body| {
+            text(dom, body, "\n  ");
+            let div = element(dom, body, "div");
+            let span = element(dom, div, "span");
+            let hello = text(dom, span, "hello");
+            server = Some((div, span, hello));
+        });
+        let (div, span, hello) = server.unwrap();
+
+        let text_entity = app.world_mut().spawn(Text::new("hello")).id();
+        let span_entity = app.world_mut().spawn(Span).add_child(text_entity).id();
+        let div_entity = app
+            .world_mut()
+            .spawn((ChildOf(body), Div))
+            .add_child(span_entity)
+            .id();
+        app.update();
+
+        assert!(errors().is_empty());
+        assert_eq!(node(&app, div_entity), div);
+        assert_eq!(node(&app, span_entity), span);
+        assert_eq!(node(&app, text_entity), hello);
+        assert!(!app.world().contains_resource::<Hydrate>());
+
+        let dom = app.world().resource::<MemoryDom>();
+        assert_eq!(dom.find_by_name(dom.document(), "div"), [div]);
+    }
+
+    #[test]
+    fn adopts_by_id() {
+        let mut server = None;
+        let (mut app, body) = app(|dom, body| {
+            let b = element(dom, body, "p");
+            dom.set_attribute(&b, HYDRATION_ID_ATTRIBUTE, "b").unwrap();
+            let a = element(dom, body, "p");
+            dom.set_attribute(&a, HYDRATION_ID_ATTRIBUTE, "a").unwrap();
+            server = Some((a, b));
+        });
+        let (a, b) = server.unwrap();
+
+        let a_entity = app
+            .world_mut()
+            .spawn((ChildOf(body), P, HydrationId::new("a")))
+            .id();
+        let b_entity = app
+            .world_mut()
+            .spawn((ChildOf(body), P, HydrationId::new("b")))
+            .id();
+        app.update();
+
+        assert!(errors().is_empty());
+        assert_eq!(node(&app, a_entity), a);
+        assert_eq!(node(&app, b_entity), b);
+
+        let body = node(&app, body);
+        let dom = app.world().resource::<MemoryDom>();
+        assert_eq!(dom.children(body), [a, b]);
+    }
+
+    #[test]
+    fn reports_mismatches() {
+        let mut server = None;
+        let (mut app, body) = app(|dom, body| {
+            let span = element(dom, body, "span");
+            let p = element(dom, body, "p");
+            server = Some((span, p));
+        });
+        let (span, p) = server.unwrap();
+
+        let div_entity = app.world_mut().spawn((ChildOf(body), Div)).id();
+        let p_entity = app.world_mut().spawn((ChildOf(body), P)).id();
+        app.update();
+
+        let errors = errors();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("expected <div>"), "{}", errors[0]);
+        assert!(errors[0].contains("found <span>"), "{}", errors[0]);
+
+        // The rest is rebuilt rather than duplicated next to the server markup.
+        let (div, new_p) = (node(&app, div_entity), node(&app, p_entity));
+        assert_ne!(new_p, p);
+
+        let body = node(&app, body);
+        let dom = app.world().resource::<MemoryDom>();
+        assert!(dom.get(span).is_none());
+        assert!(dom.get(p).is_none());
+        assert_eq!(dom.children(body), [div, new_p]);
+    }
+
+    #[test]
+    fn reports_text_mismatches() {
+        let mut server = None;
+        let (mut app, body) = app(|dom, body| {
+            let p = element(dom, body, "p");
+            server = Some((p, text(dom, p, "stale")));
+        });
+        let (p, stale) = server.unwrap();
+
+        let text_entity = app.world_mut().spawn(Text::new("fresh")).id();
+        let p_entity = app
+            .world_mut()
+            .spawn((ChildOf(body), P))
+            .add_child(text_entity)
+            .id();
+        app.update();
+
+        let errors = errors();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("\"stale\""), "{}", errors[0]);
+
+        assert_eq!(node(&app, p_entity), p);
+        assert_eq!(node(&app, text_entity), stale);
+
+        let dom = app.world().resource::<MemoryDom>();
+        assert_eq!(dom.text_content(p), "fresh");
+    }
+
+    #[test]
+    fn transparent_wrappers() {
+        let mut server = None;
+        let (mut app, body) = app(|dom, body| {
+            let span = element(dom, body, "span");
+            let p = element(dom, body, "p");
+            server = Some((span, p));
+        });
+        let (span, p) = server.unwrap();
+
+        let span_entity = app.world_mut().spawn(Span).id();
+        app.world_mut().spawn(ChildOf(body)).add_child(span_entity);
+        let p_entity = app.world_mut().spawn((ChildOf(body), P)).id();
+        app.update();
+
+        assert!(errors().is_empty());
+        assert_eq!(node(&app, span_entity), span);
+        assert_eq!(node(&app, p_entity), p);
+
+        let body = node(&app, body);
+        let dom = app.world().resource::<MemoryDom>();
+        assert_eq!(dom.children(body), [span, p]);
+    }
+}
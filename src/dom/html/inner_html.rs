@@ -1,4 +1,4 @@
-use crate::dom::{DomSystems, html::Element};
+use crate::dom::{DomSystems, backend::DomBackend};
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use std::borrow::Cow;
@@ -51,28 +51,35 @@ impl InnerHtml {
         self.0.to_mut()
     }
 
-    fn attach(attrs: Query<(&Self, Option<&Element>), Changed<Self>>) -> Result {
-        for (html, element) in &attrs {
-            let Some(element) = element else {
+    fn attach<B: DomBackend>(
+        attrs: Query<(&Self, Option<&B::NodeComponent>), Changed<Self>>,
+        mut backend: ResMut<B>,
+    ) -> Result {
+        for (html, node) in &attrs {
+            let Some(node) = node else {
                 return Err("`InnerHTML` property requires an Element".into());
             };
 
-            element.set_inner_html(html);
+            backend.set_inner_html(B::node(node), html)?;
         }
 
         Ok(())
     }
 
-    fn observe_remove(trigger: On<Remove, Self>, attr: Query<&Element>) {
-        let Ok(element) = attr.get(trigger.entity) else {
-            return;
+    fn observe_remove<B: DomBackend>(
+        trigger: On<Remove, Self>,
+        attr: Query<&B::NodeComponent>,
+        mut backend: ResMut<B>,
+    ) -> Result {
+        let Ok(node) = attr.get(trigger.entity) else {
+            return Ok(());
         };
 
-        element.set_inner_html("")
+        backend.set_inner_html(B::node(node), "")
     }
 
-    pub(super) fn plugin(app: &mut App) {
-        app.add_systems(PostUpdate, (Self::attach.in_set(DomSystems::Attach),))
-            .add_observer(Self::observe_remove);
+    pub(super) fn plugin<B: DomBackend>(app: &mut App) {
+        app.add_systems(PostUpdate, (Self::attach::<B>.in_set(DomSystems::Attach),))
+            .add_observer(Self::observe_remove::<B>);
     }
 }
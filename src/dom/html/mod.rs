@@ -1,46 +1,64 @@
-use super::{DomStartupSystems, DomSystems};
-use crate::js_err::JsErr;
+use super::{
+    DomStartupSystems, DomSystems,
+    backend::{DomBackend, WebDom, WebNode},
+};
 use bevy_app::prelude::*;
 use bevy_ecs::{lifecycle::HookContext, prelude::*, world::DeferredWorld};
-use bevy_query_observer::{AddStopObserver, Stop};
 use std::borrow::Cow;
 use wasm_bindgen::JsCast;
 
 pub mod elements;
+mod hydrate;
 mod inner_html;
 mod node_lookup;
 pub mod svg;
 
+pub use hydrate::{HYDRATION_ID_ATTRIBUTE, Hydrate, HydrationId};
 pub use inner_html::InnerHtml;
+pub(crate) use node_lookup::NodeEntityMap;
 pub use node_lookup::NodeLookup;
 
+/// The browser-only parts of the document setup.
 pub(super) struct HtmlPlugin;
 
 impl Plugin for HtmlPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((svg::SvgPlugin, InnerHtml::plugin))
-            .init_resource::<node_lookup::NodeEntityMap>()
-            .add_systems(
-                PreStartup,
-                initialize_window.in_set(DomStartupSystems::Window),
+        app.add_systems(
+            PreStartup,
+            initialize_window.in_set(DomStartupSystems::Window),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                inject_input_element,
+                inject_select_element,
+                inject_text_area_element,
             )
-            .add_systems(
-                PostUpdate,
-                (
-                    update_text,
-                    inject_element,
-                    inject_input_element,
-                    inject_select_element,
-                    inject_text_area_element,
-                    inject_text,
-                )
-                    .chain()
-                    .in_set(DomSystems::Insert),
-            )
-            .add_stop_observer(remove_text);
+                .after(inject_element::<WebDom>)
+                .in_set(DomSystems::Insert),
+        );
     }
 }
 
+pub(super) fn plugin<B: DomBackend>(app: &mut App) {
+    InnerHtml::plugin::<B>(app);
+
+    app.add_systems(
+        PostUpdate,
+        (
+            hydrate::hydrate::<B>.run_if(resource_exists::<Hydrate>),
+            update_text::<B>,
+            inject_element::<B>,
+            inject_text::<B>,
+        )
+            .chain()
+            .in_set(DomSystems::Insert),
+    )
+    .add_observer(drop_replaced_element::<B, HtmlElementName>)
+    .add_observer(drop_replaced_element::<B, svg::SvgElementName>)
+    .add_observer(detach::<B>);
+}
+
 #[doc(hidden)]
 pub use send_wrapper::SendWrapper;
 
@@ -95,7 +113,7 @@ fn initialize_window(mut commands: Commands) -> Result {
     let document_html = document.unchecked_ref::<web_sys::HtmlDocument>();
 
     commands.spawn((
-        Node(SendWrapper::new(document_node.clone())),
+        Node(WebNode::new(document_node.clone())),
         Document(SendWrapper::new(document.clone())),
         HtmlDocument(SendWrapper::new(document_html.clone())),
     ));
@@ -108,7 +126,7 @@ fn initialize_window(mut commands: Commands) -> Result {
             elements::Html,
             HtmlElement(SendWrapper::new(html.clone().unchecked_into())),
             Element(SendWrapper::new(html.clone().unchecked_into())),
-            Node(SendWrapper::new(html.unchecked_into())),
+            Node(WebNode::new(html.unchecked_into())),
         ))
         .id();
 
@@ -118,7 +136,7 @@ fn initialize_window(mut commands: Commands) -> Result {
         elements::Head,
         HtmlElement(SendWrapper::new(head.clone().unchecked_into())),
         Element(SendWrapper::new(head.clone().unchecked_into())),
-        Node(SendWrapper::new(head.unchecked_into())),
+        Node(WebNode::new(head.unchecked_into())),
     ));
 
     let body = document.body().ok_or("document body should be available")?;
@@ -128,7 +146,7 @@ fn initialize_window(mut commands: Commands) -> Result {
         elements::Body,
         HtmlElement(SendWrapper::new(body.clone())),
         Element(SendWrapper::new(body.clone().unchecked_into())),
-        Node(SendWrapper::new(body.unchecked_into())),
+        Node(WebNode::new(body.unchecked_into())),
         crate::relative_mouse::RelativeMouse::default(),
     ));
 
@@ -145,8 +163,8 @@ web_wrapper!(SvgElement);
 
 #[derive(Component)]
 #[cfg_attr(feature = "debug", derive(Debug))]
-#[component(on_replace = Self::on_remove_hook, on_insert = Self::on_insert_hook)]
-pub struct Node(SendWrapper<web_sys::Node>);
+#[component(on_replace = Self::on_replace_hook, on_insert = Self::on_insert_hook)]
+pub struct Node(pub(crate) WebNode);
 
 impl core::ops::Deref for Node {
     type Target = web_sys::Node;
@@ -163,9 +181,12 @@ impl AsRef<web_sys::Node> for Node {
 }
 
 impl Node {
-    fn on_remove_hook(world: DeferredWorld, context: HookContext) {
-        let element = world.get::<Node>(context.entity).unwrap();
-        element.0.unchecked_ref::<web_sys::Element>().remove();
+    fn on_replace_hook(mut world: DeferredWorld, context: HookContext) {
+        let node = world.get::<Node>(context.entity).unwrap().0.clone();
+
+        if let Some(mut dom) = world.get_resource_mut::<WebDom>() {
+            dom.remove(&node);
+        }
     }
 
     fn on_insert_hook(mut world: DeferredWorld, context: HookContext) {
@@ -173,8 +194,8 @@ impl Node {
         let event_target: &web_sys::EventTarget = element.0.as_ref();
         let event_target = event_target.clone();
 
-        if let Some(map) = world.get_resource::<node_lookup::NodeEntityMap>() {
-            node_lookup::register(map, &element.0, context.entity);
+        if let Some(dom) = world.get_resource::<WebDom>() {
+            dom.entities().register(&element.0, context.entity);
         }
 
         world
@@ -189,40 +210,32 @@ impl Node {
 #[cfg_attr(feature = "debug", derive(Debug))]
 #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
 #[cfg_attr(feature = "reflect", reflect(Component))]
-#[component(on_replace = Self::on_replace_hook)]
 pub struct HtmlElementName(pub &'static str);
 
-impl HtmlElementName {
-    /// Drop the element this name created, so a new name builds a new one.
-    fn on_replace_hook(mut world: DeferredWorld, context: HookContext) {
-        if let Ok(mut entity) = world.commands().get_entity(context.entity) {
-            entity.try_remove::<(
-                Node,
-                EventTarget,
-                HtmlElement,
-                Element,
-                SvgElement,
-                HtmlInputElement,
-                HtmlTextAreaElement,
-                HtmlSelectElement,
-            )>();
-        }
+/// Drop the node an element name created, so a new name builds a new one.
+fn drop_replaced_element<B: DomBackend, N: Component>(
+    trigger: On<Replace, N>,
+    mut commands: Commands,
+) {
+    if let Ok(mut entity) = commands.get_entity(trigger.entity) {
+        B::remove_node(&mut entity);
     }
 }
 
-fn inject_element(
-    elements: Query<(Entity, &HtmlElementName), Without<Node>>,
-    document: Single<&Document>,
+fn inject_element<B: DomBackend>(
+    html_elements: Query<(Entity, &HtmlElementName), Without<B::NodeComponent>>,
+    svg_elements: Query<(Entity, &svg::SvgElementName), Without<B::NodeComponent>>,
+    mut backend: ResMut<B>,
     mut commands: Commands,
 ) -> Result {
-    for (entity, element) in &elements {
-        let element = document.create_element(element.0).js_err()?;
+    for (entity, name) in &html_elements {
+        let node = backend.create_element(name.0)?;
+        backend.insert_node(&mut commands.entity(entity), node);
+    }
 
-        commands.entity(entity).insert((
-            Element(SendWrapper::new(element.clone())),
-            HtmlElement(SendWrapper::new(element.clone().unchecked_into())),
-            Node(SendWrapper::new(element.unchecked_into())),
-        ));
+    for (entity, name) in &svg_elements {
+        let node = backend.create_element_ns("http://www.w3.org/2000/svg", name.0)?;
+        backend.insert_node(&mut commands.entity(entity), node);
     }
 
     Ok(())
@@ -314,35 +327,39 @@ impl Text {
     // }
 }
 
-fn inject_text(
-    texts: Query<(Entity, &Text), Without<Node>>,
-    document: Single<&Document>,
+fn inject_text<B: DomBackend>(
+    texts: Query<(Entity, &Text), Without<B::NodeComponent>>,
+    mut backend: ResMut<B>,
     mut commands: Commands,
 ) -> Result {
     for (entity, text) in &texts {
-        let element: web_sys::Node = document.create_text_node(&text.0).unchecked_into();
-        commands
-            .entity(entity)
-            .insert(Node(SendWrapper::new(element)));
+        let node = backend.create_text(&text.0)?;
+        backend.insert_node(&mut commands.entity(entity), node);
     }
 
     Ok(())
 }
 
-fn update_text(texts: Query<(&Text, &Node), Changed<Text>>) {
+fn update_text<B: DomBackend>(
+    texts: Query<(&Text, &B::NodeComponent), Changed<Text>>,
+    mut backend: ResMut<B>,
+) -> Result {
     for (text, node) in &texts {
-        let node: &web_sys::Text = node.0.unchecked_ref();
-        node.set_data(&text.0);
+        backend.set_text(B::node(node), &text.0)?;
     }
+
+    Ok(())
 }
 
-fn remove_text(text: Stop<(&Node, &ChildOf), With<Text>>, parent: Query<&Element>) -> Result {
-    let (text, child_of) = text.into_inner();
-    let Ok(parent) = parent.get(child_of.0) else {
+/// Take a node out of its parent once its entity is no longer a child.
+fn detach<B: DomBackend>(
+    trigger: On<Remove, ChildOf>,
+    nodes: Query<&B::NodeComponent>,
+    mut backend: ResMut<B>,
+) -> Result {
+    let Ok(node) = nodes.get(trigger.entity) else {
         return Ok(());
     };
 
-    parent.remove_child(text).js_err()?;
-
-    Ok(())
+    backend.detach(B::node(node))
 }
@@ -1,3 +1,4 @@
+use crate::dom::backend::WebDom;
 use bevy_ecs::{prelude::*, system::SystemParam};
 use js_sys::WeakMap;
 use send_wrapper::SendWrapper;
@@ -6,7 +7,7 @@ use wasm_bindgen::{JsCast, JsValue};
 const GEN_BITS: u32 = 21;
 const GEN_MAX: u32 = (1 << GEN_BITS) - 1;
 
-#[derive(Resource)]
+#[cfg_attr(feature = "debug", derive(Debug))]
 pub(crate) struct NodeEntityMap(SendWrapper<WeakMap>);
 
 impl core::default::Default for NodeEntityMap {
@@ -33,19 +34,25 @@ fn decode(value: f64) -> Option<Entity> {
     Entity::try_from_bits(value as u64)
 }
 
-pub(super) fn register(map: &NodeEntityMap, node: &web_sys::Node, entity: Entity) {
-    let Some(value) = encode(entity) else { return };
-    map.0.set(node, &JsValue::from_f64(value));
+impl NodeEntityMap {
+    pub(crate) fn register(&self, node: &web_sys::Node, entity: Entity) {
+        let Some(value) = encode(entity) else { return };
+        self.0.set(node, &JsValue::from_f64(value));
+    }
+
+    pub(crate) fn get(&self, node: &web_sys::Node) -> Option<Entity> {
+        decode(self.0.get(node).as_f64()?)
+    }
 }
 
 #[derive(SystemParam)]
 pub struct NodeLookup<'w> {
-    map: Res<'w, NodeEntityMap>,
+    dom: Res<'w, WebDom>,
 }
 
 impl NodeLookup<'_> {
     pub fn get(&self, node: &web_sys::Node) -> Option<Entity> {
-        decode(self.map.0.get(node).as_f64()?)
+        self.dom.entities().get(node)
     }
 
     pub fn event_target(&self, value: impl AsRef<web_sys::Event>) -> Option<Entity> {
@@ -60,7 +67,7 @@ impl NodeLookup<'_> {
         let mut node = Some(node.clone());
 
         while let Some(next) = node {
-            match self.map.0.get(&next).as_f64() {
+            match self.dom.entities().0.get(&next).as_f64() {
                 Some(entity) => return decode(entity),
                 None => {
                     node = next.parent_node();
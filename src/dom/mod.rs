@@ -1,69 +1,78 @@
-use crate::js_err::JsErr;
+use backend::DomBackend;
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 
 pub mod attr;
+pub mod backend;
 pub mod class;
 pub mod events;
 pub mod html;
 pub mod prop;
+pub mod ssr;
 pub mod util;
 
+/// Mirrors ECS element hierarchies into the browser's document.
+///
+/// See [`backend::DomBackendPlugin`] to run against another
+/// [`backend::DomBackend`], or [`ssr::SsrPlugin`] to render on the server.
 #[derive(Default)]
 #[cfg_attr(feature = "debug", derive(Debug))]
 pub struct DomPlugin;
 
 impl Plugin for DomPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((
+        app.init_resource::<backend::WebDom>().add_plugins((
             events::EventsPlugin,
-            class::ClassPlugin,
             html::HtmlPlugin,
-            attr::AttributePlugin,
-            prop::PropPlugin,
             util::UtilsPlugin,
-        ))
-        .configure_sets(
-            PreStartup,
-            (
-                DomStartupSystems::Window,
-                DomStartupSystems::Pathname.after(DomStartupSystems::Window),
-            ),
-        )
-        .configure_sets(
-            PostUpdate,
-            (
-                DomSystems::ResolveRoutes,
-                DomSystems::Insert.after(DomSystems::ResolveRoutes),
-                DomSystems::Reparent.after(DomSystems::Insert),
-                DomSystems::Attach.after(DomSystems::Reparent),
-            ),
-        )
-        .add_systems(
-            PostUpdate,
-            reparent_incremental.in_set(DomSystems::Reparent),
-        );
+        ));
+
+        backend::plugin::<backend::WebDom>(app);
+        configure_sets(app);
     }
 }
 
-fn reparent_incremental(
-    changed_nodes: Query<(Entity, &html::Node, Option<Ref<Children>>), Changed<html::Node>>,
-    changed_children: Query<(Ref<html::Node>, &Children), Changed<Children>>,
-    nodes: Query<(Ref<html::Node>, Option<Ref<Children>>)>,
+fn configure_sets(app: &mut App) {
+    app.configure_sets(
+        PreStartup,
+        (
+            DomStartupSystems::Window,
+            DomStartupSystems::Pathname.after(DomStartupSystems::Window),
+        ),
+    )
+    .configure_sets(
+        PostUpdate,
+        (
+            DomSystems::ResolveRoutes,
+            DomSystems::Insert.after(DomSystems::ResolveRoutes),
+            DomSystems::Reparent.after(DomSystems::Insert),
+            DomSystems::Attach.after(DomSystems::Reparent),
+        ),
+    );
+}
+
+fn reparent_incremental<B: DomBackend>(
+    changed_nodes: Query<
+        (Entity, &B::NodeComponent, Option<Ref<Children>>),
+        Changed<B::NodeComponent>,
+    >,
+    changed_children: Query<(Ref<B::NodeComponent>, &Children), Changed<Children>>,
+    nodes: Query<(Ref<B::NodeComponent>, Option<Ref<Children>>)>,
     parents: Query<&ChildOf>,
-    lookup: html::NodeLookup,
+    mut backend: ResMut<B>,
 ) -> Result {
+    let backend = backend.as_mut();
+
     for (entity, node, children) in &changed_nodes {
+        let node = B::node(node);
+
         // Attach every child onto this fresh node, in `Children`
         // order. A child whose own node isn't created yet is skipped here and
-        // picked up lower down.
+        // picked up lower down. Children a hydrated node already holds in
+        // order are left in place.
         if let Some(children) = children {
             let children: &[Entity] = children.into_inner().as_ref();
-            for &child in children {
-                if let Ok((child_node, _)) = nodes.get(child) {
-                    node.append_child(&child_node).js_err()?;
-                }
-            }
+            sync_child_order(backend, node, children, &nodes)?;
         }
 
         // Splice this fresh node into its parent unless the parent
@@ -79,6 +88,12 @@ fn reparent_incremental(
         if parent_node.is_changed() {
             continue;
         }
+        let parent_node = B::node(parent_node.into_inner());
+
+        // A hydrated node was placed by the server.
+        if backend.parent(node).as_ref() == Some(parent_node) {
+            continue;
+        }
 
         let parent_children = match parent_children {
             Some(c) if !c.is_changed() => c,
@@ -98,19 +113,11 @@ fn reparent_incremental(
             .skip(1)
             .find_map(|c| {
                 let (sibling, _) = nodes.get(*c).ok()?;
-                parent_node
-                    .contains(Some(&sibling))
-                    .then(|| (**sibling).clone())
+                let sibling = B::node(sibling.into_inner());
+                (backend.parent(sibling).as_ref() == Some(parent_node)).then(|| sibling.clone())
             });
 
-        match next {
-            Some(next) => {
-                parent_node.insert_before(node, Some(&next)).js_err()?;
-            }
-            None => {
-                parent_node.append_child(node).js_err()?;
-            }
-        }
+        backend.insert_before(parent_node, node, next.as_ref())?;
     }
 
     // A parent's `Children` changed but its `Node` did not -- reconcile
@@ -119,7 +126,7 @@ fn reparent_incremental(
         if node.is_changed() {
             continue;
         }
-        sync_child_order(node.into_inner(), children.as_ref(), &nodes, &lookup)?;
+        sync_child_order(backend, B::node(node.into_inner()), children, &nodes)?;
     }
 
     Ok(())
@@ -145,79 +152,17 @@ pub enum DomSystems {
     Attach,
 }
 
-// TODO: remove when deemed unnecessary
-#[allow(dead_code)]
-fn reparent(
-    html: Query<Entity, With<html::elements::Html>>,
-    nodes: Query<(Ref<html::Node>, Option<Ref<Children>>)>,
-    lookup: html::NodeLookup,
-) -> Result {
-    fn handle_children(
-        nodes: &Query<(Ref<html::Node>, Option<Ref<Children>>)>,
-        lookup: &html::NodeLookup,
-        parent_entity: Entity,
-    ) -> Result {
-        let (node, children) = nodes.get(parent_entity)?;
-
-        let Some(children) = children else {
-            return Ok(());
-        };
-
-        let children_changed = children.is_changed();
-        let children: &[Entity] = children.into_inner().as_ref();
-        for (i, child_entity) in children.iter().enumerate() {
-            let Ok((child_node, _)) = nodes.get(*child_entity) else {
-                continue;
-            };
-
-            if node.is_changed() {
-                node.append_child(&child_node).js_err()?;
-            } else if !children_changed && child_node.is_changed() {
-                // look for the next child (that we're aware of)
-                // that's a child of the parent node
-                let next = children[i + 1..].iter().find_map(|c| {
-                    let (child_node, ..) = nodes.get(*c).ok()?;
-
-                    node.contains(Some(&child_node)).then_some(child_node)
-                });
-
-                match next {
-                    Some(next) => {
-                        node.insert_before(&child_node, Some(&next)).js_err()?;
-                    }
-                    None => {
-                        node.append_child(&child_node).js_err()?;
-                    }
-                }
-            }
-
-            handle_children(nodes, lookup, *child_entity)?;
-        }
-
-        if children_changed && !node.is_changed() {
-            sync_child_order(node.into_inner(), children, nodes, lookup)?;
-        }
-
-        Ok(())
-    }
-
-    let html = html.single()?;
-    handle_children(&nodes, &lookup, html)?;
-
-    Ok(())
-}
-
 /// Make the DOM order of `parent`'s entity-backed children match their
 /// `Children` order, attaching any that aren't in the DOM yet. Nodes on a
 /// longest increasing subsequence of the current order stay put, so the
 /// number of `insert_before` calls (each a remove+insert that drops focus
 /// and restarts animations on the moved node) is minimal. When the DOM
 /// already matches, this only reads sibling pointers.
-fn sync_child_order(
-    parent: &html::Node,
+fn sync_child_order<B: DomBackend>(
+    backend: &mut B,
+    parent: &B::Node,
     children: &[Entity],
-    nodes: &Query<(Ref<html::Node>, Option<Ref<Children>>)>,
-    lookup: &html::NodeLookup,
+    nodes: &Query<(Ref<B::NodeComponent>, Option<Ref<Children>>)>,
 ) -> Result {
     use bevy_platform::collections::{HashMap, HashSet};
 
@@ -232,20 +177,20 @@ fn sync_child_order(
         };
 
         desired_index.insert(*child, desired.len());
-        desired.push((**child_node).clone());
+        desired.push(B::node(child_node.into_inner()).clone());
     }
 
     // The current order, as desired-indices of the parent's DOM children.
     // Foreign nodes (not entity-backed, or not in `children`) are skipped;
     // moves are anchored on managed nodes only, so they stay where they are.
     let mut current = Vec::with_capacity(desired.len());
-    let mut dom_child = parent.first_child();
+    let mut dom_child = backend.first_child(parent);
     while let Some(node) = dom_child {
-        if let Some(index) = lookup.get(&node).and_then(|e| desired_index.get(&e)) {
+        if let Some(index) = backend.entity(&node).and_then(|e| desired_index.get(&e)) {
             current.push(*index);
         }
 
-        dom_child = node.next_sibling();
+        dom_child = backend.next_sibling(&node);
     }
 
     let in_order = current.len() == desired.len() && current.is_sorted();
@@ -257,14 +202,14 @@ fn sync_child_order(
         .into_iter()
         .collect();
 
-    let mut anchor: Option<web_sys::Node> = None;
+    let mut anchor: Option<B::Node> = None;
     for (i, node) in desired.iter().enumerate().rev() {
         if keep.contains(&i) {
             anchor = Some(node.clone());
             continue;
         }
 
-        parent.insert_before(node, anchor.as_ref()).js_err()?;
+        backend.insert_before(parent, node, anchor.as_ref())?;
         anchor = Some(node.clone());
     }
 
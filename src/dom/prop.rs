@@ -1,27 +1,36 @@
-use crate::dom::{DomSystems, html::Node};
+use crate::dom::{
+    DomSystems,
+    backend::{DomBackend, PropValue, WebDom},
+};
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
-use send_wrapper::SendWrapper;
 use wasm_bindgen::JsValue;
 
+/// Sets the built-in properties on browser nodes.
+///
+/// [`DomPlugin`](crate::dom::DomPlugin) already installs this.
 pub struct PropPlugin;
 
 impl Plugin for PropPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            PostUpdate,
-            (
-                Value::resolve_props,
-                Checked::resolve_props,
-                Selected::resolve_props,
-                TextContent::resolve_props,
-            )
-                .after(DomSystems::Insert)
-                .before(DomSystems::Attach),
-        );
+        plugin::<WebDom>(app);
     }
 }
 
+pub(super) fn plugin<B: DomBackend>(app: &mut App) {
+    app.add_systems(
+        PostUpdate,
+        (
+            Value::resolve_props::<B>,
+            Checked::resolve_props::<B>,
+            Selected::resolve_props::<B>,
+            TextContent::resolve_props::<B>,
+        )
+            .after(DomSystems::Insert)
+            .before(DomSystems::Attach),
+    );
+}
+
 pub trait Prop {
     const NAME: &'static str;
     type Value: Into<JsValue> + Clone + Send + Sync + 'static;
@@ -51,15 +60,15 @@ where
 }
 
 impl<P: Prop + 'static> PropContainer<P> {
-    fn resolve_props(
-        props: Query<(&Node, &Self), Or<(Changed<Node>, Changed<Self>)>>,
-        mut target: Local<Option<SendWrapper<JsValue>>>,
-    ) {
-        let target = target.get_or_insert_with(|| SendWrapper::new(P::NAME.into()));
-
+    fn resolve_props<B: DomBackend>(
+        props: Query<(&B::NodeComponent, &Self), Or<(Changed<B::NodeComponent>, Changed<Self>)>>,
+        mut backend: ResMut<B>,
+    ) where
+        P::Value: Into<PropValue>,
+    {
         for (node, prop) in props {
-            if let Err(e) = js_sys::Reflect::set(node, target, &prop.0.clone().into()) {
-                log::error!("failed to set property: {e:?}");
+            if let Err(e) = backend.set_property(B::node(node), P::NAME, prop.0.clone().into()) {
+                log::error!("failed to set property: {e}");
             }
         }
     }
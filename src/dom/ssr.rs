@@ -0,0 +1,414 @@
+//! Render an app's element hierarchy to HTML without a browser.
+//!
+//! [`SsrPlugin`] spawns the same `Html`, `Head`, and `Body` entities as the
+//! browser setup, without creating any nodes. Build the page as usual, run
+//! `app.update()`, then serialize it with [`render_document`]. The output can be adopted in the browser by inserting
+//! [`Hydrate`](super::html::Hydrate) before the first update.
+
+use super::{
+    DomStartupSystems,
+    attr::{Attributes, Data, Download},
+    class::{Class, Classes},
+    html::{
+        HYDRATION_ID_ATTRIBUTE, HtmlElementName, HydrationId, InnerHtml, Text, elements,
+        svg::SvgElementName,
+    },
+    prop,
+};
+use bevy_app::prelude::*;
+use bevy_ecs::{prelude::*, world::EntityRefExcept};
+
+/// Elements that never have children or a closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Elements whose text children are written without escaping.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// Only spawn the document's root elements, so the app can be rendered to
+/// a string with [`render_document`].
+#[derive(Default)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct SsrPlugin;
+
+impl Plugin for SsrPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreStartup,
+            initialize_document.in_set(DomStartupSystems::Window),
+        );
+
+        super::configure_sets(app);
+    }
+}
+
+fn initialize_document(mut commands: Commands) {
+    let html = commands.spawn(elements::Html).id();
+    commands.spawn((ChildOf(html), elements::Head));
+    commands.spawn((ChildOf(html), elements::Body));
+}
+
+/// Serialize the whole document, from the `Html` root down.
+pub fn render_document(world: &mut World) -> Result<String> {
+    let html = world
+        .query_filtered::<Entity, With<elements::Html>>()
+        .single(world)?;
+
+    let mut out = String::from("<!DOCTYPE html>");
+    render_into(world, html, &mut out)?;
+
+    Ok(out)
+}
+
+/// Serialize `entity` and its descendants.
+pub fn render_entity(world: &mut World, entity: Entity) -> Result<String> {
+    let mut out = String::new();
+    render_into(world, entity, &mut out)?;
+
+    Ok(out)
+}
+
+fn render_into(world: &mut World, entity: Entity, out: &mut String) -> Result {
+    let mut state = world.query::<(Option<&Attributes>, EntityRefExcept<Attributes>)>();
+    let query = state.query(world);
+
+    write_node(&query, entity, None, out)
+}
+
+/// `raw_text` is the name of the enclosing `script` or `style` element, if any.
+fn write_node(
+    query: &Query<(Option<&Attributes>, EntityRefExcept<Attributes>)>,
+    entity: Entity,
+    raw_text: Option<&str>,
+    out: &mut String,
+) -> Result {
+    let (attributes, entity_ref) = query.get(entity)?;
+
+    if let Some(text) = entity_ref.get::<Text>() {
+        write_text(text, raw_text, out);
+        return Ok(());
+    }
+
+    let name = entity_ref
+        .get::<HtmlElementName>()
+        .map(|n| n.0)
+        .or_else(|| entity_ref.get::<SvgElementName>().map(|n| n.0));
+
+    // Entities without an element, like route containers that haven't matched,
+    // don't produce markup of their own.
+    let Some(name) = name else {
+        return write_children(query, &entity_ref, raw_text, out);
+    };
+
+    out.push('<');
+    out.push_str(name);
+
+    for attr in attributes.iter().flat_map(|a| a.iter(&entity_ref)) {
+        write_attribute(attr.name(), &attr.value(), out);
+    }
+
+    if let Some(id) = entity_ref.get::<HydrationId>() {
+        write_attribute(HYDRATION_ID_ATTRIBUTE, &id.0, out);
+    }
+
+    if let Some(data) = entity_ref.get::<Data>() {
+        write_attribute(&data.attribute_string(), data.value(), out);
+    }
+
+    match entity_ref.get::<Download>() {
+        Some(Download::Filename(filename)) => write_attribute("download", filename, out),
+        Some(Download::Auto) => write_attribute("download", "", out),
+        None => {}
+    }
+
+    // Properties have no markup, so write the attributes that set the same
+    // initial state. A text area's value is its content instead.
+    if let Some(value) = entity_ref.get::<prop::Value>()
+        && name != "textarea"
+    {
+        write_attribute("value", &value.0, out);
+    }
+
+    if entity_ref.get::<prop::Checked>().is_some_and(|c| c.0) {
+        write_attribute("checked", "", out);
+    }
+
+    if entity_ref.get::<prop::Selected>().is_some_and(|s| s.0) {
+        write_attribute("selected", "", out);
+    }
+
+    let classes = entity_ref
+        .get::<Classes>()
+        .iter()
+        .flat_map(|c| c.iter())
+        .filter_map(|c| query.get(c).ok()?.1.get::<Class>())
+        .map(|c| &**c)
+        .collect::<Vec<_>>();
+    if !classes.is_empty() {
+        write_attribute("class", &classes.join(" "), out);
+    }
+
+    out.push('>');
+
+    if VOID_ELEMENTS.contains(&name) {
+        return Ok(());
+    }
+
+    let raw_text = RAW_TEXT_ELEMENTS.contains(&name).then_some(name);
+    let text_content = entity_ref
+        .get::<prop::TextContent>()
+        .map(|t| &t.0)
+        .or_else(|| {
+            let value = entity_ref.get::<prop::Value>();
+            value.filter(|_| name == "textarea").map(|v| &v.0)
+        });
+
+    // Like the browser, inner HTML or text content takes the place of any
+    // children.
+    match (entity_ref.get::<InnerHtml>(), text_content) {
+        (Some(html), _) => out.push_str(html),
+        (None, Some(text)) => write_text(text, raw_text, out),
+        (None, None) => write_children(query, &entity_ref, raw_text, out)?,
+    }
+
+    out.push_str("</");
+    out.push_str(name);
+    out.push('>');
+
+    Ok(())
+}
+
+fn write_children(
+    query: &Query<(Option<&Attributes>, EntityRefExcept<Attributes>)>,
+    entity_ref: &EntityRefExcept<Attributes>,
+    raw_text: Option<&str>,
+    out: &mut String,
+) -> Result {
+    let mut previous_text = false;
+    for child in entity_ref.get::<Children>().iter().flat_map(|c| c.iter()) {
+        // The browser would merge adjacent text into one node, so keep them
+        // apart for hydration.
+        let is_text = query.get(child).is_ok_and(|(_, c)| c.contains::<Text>());
+        if is_text && previous_text && raw_text.is_none() {
+            out.push_str("<!---->");
+        }
+        previous_text = is_text;
+
+        write_node(query, child, raw_text, out)?;
+    }
+
+    Ok(())
+}
+
+fn write_attribute(name: &str, value: &str, out: &mut String) {
+    out.push(' ');
+    out.push_str(name);
+
+    // Boolean attributes are present with an empty value.
+    if !value.is_empty() {
+        out.push_str("=\"");
+        escape_attribute(value, out);
+        out.push('"');
+    }
+}
+
+fn write_text(text: &str, raw_text: Option<&str>, out: &mut String) {
+    match raw_text {
+        Some(element) => escape_raw_text(text, element, out),
+        None => escape_text(text, out),
+    }
+}
+
+fn escape_text(text: &str, out: &mut String) {
+    for char in text.chars() {
+        match char {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\u{a0}' => out.push_str("&nbsp;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Keep `text` from closing its `element`, a `script` or `style`, early.
+///
+/// Only the closing tag the parser would stop at, matched regardless of case,
+/// is rewritten. `<\/` reads the same as `</` in the strings, regular
+/// expressions and comments where it can appear in either language.
+fn escape_raw_text(text: &str, element: &str, out: &mut String) {
+    let mut rest = text;
+    while let Some(index) = rest.find("</") {
+        let after = &rest[index + 2..];
+        let closes = after
+            .get(..element.len())
+            .is_some_and(|name| name.eq_ignore_ascii_case(element));
+
+        out.push_str(&rest[..index]);
+        out.push_str(if closes { "<\\/" } else { "</" });
+        rest = after;
+    }
+
+    out.push_str(rest);
+}
+
+fn escape_attribute(value: &str, out: &mut String) {
+    for char in value.chars() {
+        match char {
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            '\u{a0}' => out.push_str("&nbsp;"),
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dom::prelude::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(SsrPlugin);
+        app.update();
+        app
+    }
+
+    fn body(app: &mut App) -> Entity {
+        app.world_mut()
+            .query_filtered::<Entity, With<Body>>()
+            .single(app.world())
+            .unwrap()
+    }
+
+    #[test]
+    fn empty_document() {
+        let mut app = app();
+
+        assert_eq!(
+            render_document(app.world_mut()).unwrap(),
+            "<!DOCTYPE html><html><head></head><body></body></html>"
+        );
+    }
+
+    #[test]
+    fn elements_attributes_and_classes() {
+        let mut app = app();
+        let body = body(&mut app);
+
+        let div = app
+            .world_mut()
+            .spawn((
+                ChildOf(body),
+                Div,
+                attr::Href::new("/a?b=1&c=\"2\""),
+                class!["card", "wide"],
+                children![
+                    (Input, attr::Disabled),
+                    Br,
+                    (Span, children![Text::new("1 < 2 & 3")]),
+                ],
+            ))
+            .id();
+        app.update();
+
+        assert_eq!(
+            render_entity(app.world_mut(), div).unwrap(),
+            "<div href=\"/a?b=1&amp;c=&quot;2&quot;\" class=\"card wide\">\
+             <input disabled><br><span>1 &lt; 2 &amp; 3</span></div>"
+        );
+    }
+
+    #[test]
+    fn raw_content() {
+        let mut app = app();
+        let body = body(&mut app);
+
+        let style = app
+            .world_mut()
+            .spawn((ChildOf(body), Style, children![Text::new("a > b {}")]))
+            .id();
+        let p = app
+            .world_mut()
+            .spawn((ChildOf(body), P, InnerHtml::new("<b>bold</b>")))
+            .id();
+        app.update();
+
+        assert_eq!(
+            render_entity(app.world_mut(), style).unwrap(),
+            "<style>a > b {}</style>"
+        );
+        assert_eq!(
+            render_entity(app.world_mut(), p).unwrap(),
+            "<p><b>bold</b></p>"
+        );
+
+        let script = app
+            .world_mut()
+            .spawn((
+                ChildOf(body),
+                Script,
+                children![Text::new("let s = \"</SCRIPT></b>\"; a </style")],
+            ))
+            .id();
+        app.update();
+
+        assert_eq!(
+            render_entity(app.world_mut(), script).unwrap(),
+            "<script>let s = \"<\\/SCRIPT></b>\"; a </style</script>"
+        );
+    }
+
+    #[test]
+    fn props() {
+        let mut app = app();
+        let body = body(&mut app);
+
+        let form = app
+            .world_mut()
+            .spawn((
+                ChildOf(body),
+                Form,
+                children![
+                    (Input, prop::Value::new("a & b".into())),
+                    (Input, prop::Checked::new(true)),
+                    (Input, prop::Checked::new(false)),
+                    (TextArea, prop::Value::new("<hi>".into())),
+                    (P, prop::TextContent::new("text".into())),
+                ],
+            ))
+            .id();
+        app.update();
+
+        assert_eq!(
+            render_entity(app.world_mut(), form).unwrap(),
+            "<form><input value=\"a &amp; b\"><input checked><input>\
+             <textarea>&lt;hi&gt;</textarea><p>text</p></form>"
+        );
+    }
+
+    #[test]
+    fn hydration_markers() {
+        let mut app = app();
+        let body = body(&mut app);
+
+        let p = app
+            .world_mut()
+            .spawn((
+                ChildOf(body),
+                P,
+                HydrationId::new("intro"),
+                children![Text::new("a"), Text::new("b")],
+            ))
+            .id();
+        app.update();
+
+        assert_eq!(
+            render_entity(app.world_mut(), p).unwrap(),
+            "<p data-bweb-hid=\"intro\">a<!---->b</p>"
+        );
+    }
+}
@@ -11,6 +11,7 @@ pub mod dom;
 pub mod js_err;
 pub mod relative_mouse;
 pub mod runner;
+pub mod shortcut;
 pub mod task;
 pub mod time;
 
@@ -20,11 +21,15 @@ pub mod router;
 pub mod prelude {
     pub use crate::dom::prelude::*;
     pub use crate::js_err::JsErr;
+    pub use crate::shortcut::{ActiveShortcuts, Shortcut, ShortcutOf, ShortcutPlugin, Shortcuts};
     pub use crate::task::{TaskComponent, TaskWorld, spawn_local};
     pub use crate::time::sleep;
 
     #[cfg(feature = "router")]
-    pub use crate::router::{OpenNewTab, Route, query::QueryParams};
+    pub use crate::router::{
+        ActiveLink, OpenNewTab, Params, Route, RouteData, RouteGuard,
+        query::{QueryParams, QueryView},
+    };
 
     pub use crate::BwebPlugins;
 }
@@ -0,0 +1,221 @@
+//! The state the router keeps in browser history entries, and scroll
+//! restoration built on it.
+
+use crate::{
+    dom::{attr, html::Element},
+    prelude::Window,
+};
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::HashMap;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use wasm_bindgen::JsValue;
+
+/// Keep the page's scroll offset when an anchor with this component is
+/// followed, rather than scrolling to the top or to the URL's fragment.
+#[derive(Component, Default, Clone, Copy)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+#[cfg_attr(feature = "reflect", reflect(Component))]
+pub struct PreserveScroll;
+
+/// The state payload of the current history entry, as passed in
+/// [`NavigateOptions::state`](super::NavigateOptions::state).
+///
+/// This follows the user through back and forward navigation, so it suits
+/// transient data that shouldn't be in the URL, like whether a modal is open.
+#[derive(Resource, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+#[cfg_attr(feature = "reflect", reflect(Resource))]
+pub struct HistoryState(#[cfg_attr(feature = "reflect", reflect(ignore))] pub(super) Option<Value>);
+
+impl HistoryState {
+    /// Deserialize the payload, or `None` when the entry doesn't have one.
+    pub fn get<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        let Some(value) = &self.0 else {
+            return Ok(None);
+        };
+
+        Ok(Some(T::deserialize(value)?))
+    }
+
+    /// The payload as it's stored in the history entry.
+    pub fn value(&self) -> Option<&Value> {
+        self.0.as_ref()
+    }
+}
+
+/// The router's part of a history entry's state object.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "debug"), derive(Debug))]
+pub(super) struct HistoryEntry {
+    /// Identifies the entry across back and forward navigations.
+    pub key: String,
+    /// The window's scroll offset when the entry was last left.
+    #[serde(default)]
+    pub scroll: (f64, f64),
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<Value>,
+}
+
+impl HistoryEntry {
+    pub fn new(state: Option<Value>) -> Self {
+        let key = (js_sys::Math::random() * (1u64 << 53) as f64) as u64;
+
+        Self {
+            key: format!("{key:x}"),
+            scroll: (0.0, 0.0),
+            state,
+        }
+    }
+
+    /// The entry the browser is currently on, if the router wrote it.
+    pub fn current() -> Option<Self> {
+        let state = web_sys::window()?.history().ok()?.state().ok()?;
+        Self::from_js(&state)
+    }
+
+    pub fn to_js(&self) -> JsValue {
+        // Plain objects rather than maps, so the state survives structured
+        // cloning into the browser's history.
+        self.serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .unwrap_or(JsValue::NULL)
+    }
+
+    pub fn from_js(state: &JsValue) -> Option<Self> {
+        serde_wasm_bindgen::from_value(state.clone()).ok()
+    }
+}
+
+/// Push a new history entry for `href`.
+pub(super) fn push_entry(href: &str, entry: &HistoryEntry) {
+    if let Some(history) = web_sys::window().and_then(|w| w.history().ok()) {
+        let _ = history.push_state_with_url(&entry.to_js(), "", Some(href));
+    }
+}
+
+/// Replace the current history entry. Without an `href`, its URL is kept.
+pub(super) fn replace_entry(href: Option<&str>, entry: &HistoryEntry) {
+    if let Some(history) = web_sys::window().and_then(|w| w.history().ok()) {
+        let _ = history.replace_state_with_url(&entry.to_js(), "", href);
+    }
+}
+
+pub(super) fn window_scroll() -> (f64, f64) {
+    web_sys::window()
+        .and_then(|w| Some((w.scroll_x().ok()?, w.scroll_y().ok()?)))
+        .unwrap_or_default()
+}
+
+/// Scroll offsets of the entries this session has left, by key.
+///
+/// Entries left with a push also carry their offset in their state, but an
+/// entry left by going back or forward can no longer be written to.
+#[derive(Resource, Default)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub(super) struct ScrollPositions(pub HashMap<String, (f64, f64)>);
+
+/// Where to scroll once the next navigation has rendered.
+#[derive(Resource, Default)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub(super) struct PendingScroll(pub Option<ScrollTarget>);
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub(super) enum ScrollTarget {
+    Top,
+    /// The element whose [`Id`](attr::Id) matches, or the top if none does.
+    Fragment(String),
+    Position(f64, f64),
+}
+
+impl ScrollTarget {
+    /// The top of the page, or the element named by `url`'s fragment.
+    pub fn for_url(url: &web_sys::Url) -> Self {
+        Self::for_hash(&url.hash())
+    }
+
+    fn for_hash(hash: &str) -> Self {
+        match hash.strip_prefix('#') {
+            Some(id) if !id.is_empty() => Self::Fragment(id.to_string()),
+            _ => Self::Top,
+        }
+    }
+}
+
+pub(super) fn apply_scroll(
+    mut pending: ResMut<PendingScroll>,
+    ids: Query<(&attr::Id, &Element)>,
+    window: Single<&Window>,
+) {
+    let Some(target) = pending.0.take() else {
+        return;
+    };
+
+    let (x, y) = match target {
+        ScrollTarget::Top => (0.0, 0.0),
+        ScrollTarget::Position(x, y) => (x, y),
+        ScrollTarget::Fragment(id) => {
+            if let Some((_, element)) = ids.iter().find(|(i, _)| ***i == *id) {
+                element.scroll_into_view();
+                return;
+            }
+
+            (0.0, 0.0)
+        }
+    };
+
+    window.scroll_to_with_x_and_y(x, y);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Modal {
+        open: bool,
+        tab: String,
+    }
+
+    #[test]
+    fn entry_round_trip() {
+        let modal = Modal {
+            open: true,
+            tab: "settings".to_string(),
+        };
+        let entry = HistoryEntry {
+            key: "1f".to_string(),
+            scroll: (12.0, 340.5),
+            state: Some(serde_json::to_value(&modal).unwrap()),
+        };
+
+        let json = serde_json::to_value(&entry).unwrap();
+        let read: HistoryEntry = serde_json::from_value(json).unwrap();
+        assert_eq!(read, entry);
+
+        let state = HistoryState(read.state);
+        assert_eq!(state.get::<Modal>().unwrap(), Some(modal));
+        assert!(state.get::<u32>().is_err());
+    }
+
+    #[test]
+    fn entry_defaults() {
+        let read: HistoryEntry = serde_json::from_str(r#"{"key":"a"}"#).unwrap();
+        assert_eq!(read.scroll, (0.0, 0.0));
+        assert_eq!(HistoryState(read.state).get::<Modal>().unwrap(), None);
+
+        // Entries pushed by something other than the router.
+        assert!(serde_json::from_str::<HistoryEntry>(r#"{"open":true}"#).is_err());
+    }
+
+    #[test]
+    fn fragment_targets() {
+        assert!(matches!(ScrollTarget::for_hash(""), ScrollTarget::Top));
+        assert!(matches!(ScrollTarget::for_hash("#"), ScrollTarget::Top));
+        assert!(matches!(
+            ScrollTarget::for_hash("#comments"),
+            ScrollTarget::Fragment(id) if id == "comments"
+        ));
+    }
+}
@@ -0,0 +1,146 @@
+//! Marking the anchors that point at the current route.
+
+use super::{Pathname, RouterLocation, resolve_anchor};
+use crate::dom::prelude::*;
+use crate::js_err::JsErr;
+use bevy_ecs::prelude::*;
+
+/// Mark an [`A`] element while it links to the current [`Pathname`], adding
+/// its class and `aria-current="page"`.
+///
+/// The anchor's `href` is resolved as it is for navigation, so relative links
+/// and the [base path](super::RouterPlugin::with_base) are accounted for.
+///
+/// ```ignore
+/// (A, attr::Href::new("/posts"), ActiveLink::new(Class::new("active")).prefix())
+/// ```
+#[derive(Component, Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct ActiveLink {
+    class: Class,
+    exact: bool,
+}
+
+impl Default for ActiveLink {
+    fn default() -> Self {
+        Self::new(Class::new("active"))
+    }
+}
+
+impl ActiveLink {
+    /// Add `class` while the link's path is exactly the current path.
+    pub fn new(class: Class) -> Self {
+        Self { class, exact: true }
+    }
+
+    /// Also mark the link while the current path is nested under its path,
+    /// so `/posts` stays active on `/posts/12`. A link to `/` still needs an
+    /// exact match.
+    pub fn prefix(mut self) -> Self {
+        self.exact = false;
+        self
+    }
+}
+
+/// The class entity added to an active link.
+#[derive(Component)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub(super) struct Active(Entity);
+
+/// Whether a link to `link` is active on `current`. Prefix matches only stop
+/// at segment boundaries, and a link to the root is always exact, since every
+/// path is nested under it.
+fn path_matches(link: &str, current: &str, exact: bool) -> bool {
+    let link = link.trim_end_matches('/');
+    let current = current.trim_end_matches('/');
+
+    if exact || link.is_empty() {
+        return link == current;
+    }
+
+    match current.strip_prefix(link) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+pub(super) fn update_active_links(
+    links: Query<(
+        Entity,
+        Ref<ActiveLink>,
+        Ref<attr::Href>,
+        Has<attr::Download>,
+        Option<&attr::Target>,
+        Option<&Active>,
+    )>,
+    pathname: Res<Pathname>,
+    window: Single<&Window>,
+    location: Res<RouterLocation>,
+    mut commands: Commands,
+) -> Result {
+    let mut current = None;
+
+    for (entity, link, href, download, target, active) in &links {
+        if !pathname.is_changed() && !link.is_changed() && !href.is_changed() {
+            continue;
+        }
+
+        let current = match &current {
+            Some(current) => current,
+            None => current.insert(window.location().href().js_err()?),
+        };
+        let is_active = resolve_anchor(&location, current, &href, download, target)?
+            .is_some_and(|url| path_matches(&url.pathname(), pathname.pathname(), link.exact));
+
+        // Start over if the class changed.
+        if let Some(Active(class)) = active
+            && (!is_active || link.is_changed())
+        {
+            commands.entity(*class).despawn();
+            commands
+                .entity(entity)
+                .remove::<(Active, attr::AriaCurrent)>();
+        }
+
+        if is_active && (active.is_none() || link.is_changed()) {
+            let class = commands.spawn((ClassOf(entity), link.class.clone())).id();
+            commands
+                .entity(entity)
+                .insert((Active(class), attr::AriaCurrent::new("page")));
+        }
+    }
+
+    Ok(())
+}
+
+/// Clean up after an anchor stops being an [`ActiveLink`].
+pub(super) fn remove_active_link(
+    ev: On<Remove, ActiveLink>,
+    active: Query<&Active>,
+    mut commands: Commands,
+) {
+    if let Ok(Active(class)) = active.get(ev.entity) {
+        commands.entity(*class).try_despawn();
+        commands
+            .entity(ev.entity)
+            .try_remove::<(Active, attr::AriaCurrent)>();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::path_matches;
+
+    #[test]
+    fn test_active_matching() {
+        assert!(path_matches("/posts", "/posts", true));
+        assert!(path_matches("/posts/", "/posts", true));
+        assert!(!path_matches("/posts", "/posts/12", true));
+
+        assert!(path_matches("/posts", "/posts/12", false));
+        assert!(!path_matches("/posts", "/postscript", false));
+        assert!(!path_matches("/", "/about", false));
+        assert!(path_matches("/", "/", false));
+        assert!(path_matches("/", "/", true));
+    }
+}
@@ -0,0 +1,236 @@
+//! Asynchronous data loading for routes.
+
+use super::prefetch::{self, Claimed, PrefetchKey};
+use super::{Pathname, RouteElement, clear_rendered, resolve_current, resolve_under};
+use crate::task::{TaskComponent, TaskWorld};
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::RunSystemError;
+use core::{future::Future, pin::Pin};
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicU64, Ordering},
+};
+
+/// The output of a route's loader. It's a resource while the route's element
+/// renders, then moves onto the entity rendered into, and is removed along
+/// with what the route rendered.
+///
+/// ```ignore
+/// Route::new()
+///     .route("/posts/:id", |post: Res<RouteData<Post>>| render_post(&post))
+///     .loader(|params: Params<PostParams>| {
+///         let id = params.get().map(|p| p.id);
+///         async move { fetch_post(id?).await }
+///     })
+///     .pending(|| Text::new("Loading..."))
+/// ```
+#[derive(Resource, Component)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct RouteData<T: Send + Sync + 'static>(pub T);
+
+impl<T: Send + Sync + 'static> core::ops::Deref for RouteData<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Renders a route's element into an entity, with a loader's output.
+pub(super) type Loaded = Box<dyn FnOnce(&mut World, Entity, &RouteElement)>;
+pub(super) type LoadFuture = Pin<Box<dyn Future<Output = Result<Loaded>>>>;
+
+#[derive(Clone)]
+pub(super) struct RouteLoader(
+    Arc<Mutex<dyn FnMut(&mut World) -> Result<LoadFuture> + Send + Sync>>,
+);
+
+impl RouteLoader {
+    pub fn new<S, F, T, M>(loader: S) -> Self
+    where
+        S: IntoSystem<(), F, M>,
+        S::System: Send + Sync + 'static,
+        F: Future<Output = Result<T>> + 'static,
+        T: Send + Sync + 'static,
+    {
+        let mut system = IntoSystem::into_system(loader);
+        let mut initialized = false;
+
+        Self(Arc::new(Mutex::new(move |world: &mut World| {
+            if !initialized {
+                system.initialize(world);
+                initialized = true;
+            }
+
+            let future = system.run((), world).map_err(|e| match e {
+                RunSystemError::Failed(f) => f,
+                RunSystemError::Skipped(s) => s.into(),
+            })?;
+            let future: LoadFuture = Box::pin(async move {
+                let data = future.await?;
+                let loaded: Loaded = Box::new(
+                    move |world: &mut World, target: Entity, element: &RouteElement| {
+                        world.insert_resource(RouteData(data));
+                        (element.0.lock().unwrap())(world, target);
+
+                        if let Some(data) = world.remove_resource::<RouteData<T>>() {
+                            world.entity_mut(target).insert(data);
+                        }
+                    },
+                );
+
+                Ok(loaded)
+            });
+
+            Ok(future)
+        })))
+    }
+}
+
+/// Renders a failed load, given its error.
+#[derive(Clone)]
+pub(super) struct RouteErrorElement(
+    Arc<Mutex<dyn FnMut(&mut World, Entity, BevyError) -> Result + Send + Sync>>,
+);
+
+impl RouteErrorElement {
+    pub fn new<F, B, M>(element: F) -> Self
+    where
+        F: IntoSystem<In<BevyError>, B, M>,
+        F::System: Send + Sync + 'static,
+        B: Bundle,
+    {
+        let mut system = IntoSystem::into_system(element);
+
+        Self(Arc::new(Mutex::new(
+            move |world: &mut World, entity: Entity, error: BevyError| {
+                system.initialize(world);
+                let bundle = system.run(error, world).map_err(|e| match e {
+                    RunSystemError::Failed(f) => f,
+                    RunSystemError::Skipped(s) => s.into(),
+                })?;
+                world.entity_mut(entity).insert(bundle);
+
+                Ok(())
+            },
+        )))
+    }
+}
+
+/// The load in flight for the entity a route renders into. Removing it, as
+/// happens when the route stops matching, cancels the load, or abandons the
+/// prefetch it's waiting on.
+#[derive(Component)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub(super) struct RouteLoad {
+    id: u64,
+    _task: Option<TaskComponent>,
+}
+
+/// Where a load's result goes once it settles.
+pub(super) struct Claimant {
+    target: Entity,
+    id: u64,
+    element: RouteElement,
+    error: Option<RouteErrorElement>,
+    /// The current path when the load started.
+    pathname: String,
+    /// The part of it left for routes nested in the element.
+    remainder: String,
+}
+
+/// Start `loader`'s future with the current [`RouteParams`](super::RouteParams).
+pub(super) fn run(loader: &RouteLoader, world: &mut World) -> Result<LoadFuture> {
+    (loader.0.lock().unwrap())(world)
+}
+
+/// Render `pending` into `target`, then start `loader` and render `element`,
+/// or `error` on failure, once it settles. `remainder` is the part of the path
+/// left for the routes nested in `element`.
+///
+/// A result prefetched for `key` is used instead, if there is one.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn start(
+    world: &mut World,
+    target: Entity,
+    key: PrefetchKey,
+    loader: &RouteLoader,
+    pending: Option<&RouteElement>,
+    element: RouteElement,
+    error: Option<RouteErrorElement>,
+    remainder: &str,
+) -> Result {
+    static NEXT_LOAD: AtomicU64 = AtomicU64::new(0);
+
+    let id = NEXT_LOAD.fetch_add(1, Ordering::Relaxed);
+    let claimant = Claimant {
+        target,
+        id,
+        element,
+        error,
+        pathname: world.resource::<Pathname>().pathname().to_string(),
+        remainder: remainder.to_string(),
+    };
+
+    let claimant = match prefetch::claim(world, &key, claimant) {
+        Claimed::Ready(loaded, claimant) => return apply(world, &claimant, Ok(loaded)),
+        Claimed::Waiting => None,
+        Claimed::Missed(claimant) => Some(claimant),
+    };
+
+    if let Some(pending) = pending {
+        (pending.0.lock().unwrap())(world, target);
+    }
+
+    let task = match claimant {
+        Some(claimant) => {
+            let future = run(loader, world)?;
+            Some(TaskComponent::new(
+                async move |mut world: TaskWorld| -> Result {
+                    let result = future.await;
+                    world.with(move |world| finish(world, claimant, result))
+                },
+            ))
+        }
+        None => None,
+    };
+    world
+        .entity_mut(target)
+        .insert(RouteLoad { id, _task: task });
+
+    Ok(())
+}
+
+/// Replace the pending element with the load's result, unless a later
+/// navigation superseded it.
+pub(super) fn finish(world: &mut World, claimant: Claimant, result: Result<Loaded>) -> Result {
+    if world
+        .get::<RouteLoad>(claimant.target)
+        .is_none_or(|load| load.id != claimant.id)
+    {
+        return Ok(());
+    }
+
+    clear_rendered(world, claimant.target)?;
+    apply(world, &claimant, result)?;
+
+    // Routes nested in the new element haven't seen the current path yet.
+    // Those elsewhere have, unless it changed while loading.
+    if world.resource::<Pathname>().pathname() == claimant.pathname {
+        resolve_under(world, claimant.target, &claimant.remainder)?;
+    } else {
+        resolve_current(world)?;
+    }
+
+    Ok(())
+}
+
+fn apply(world: &mut World, claimant: &Claimant, result: Result<Loaded>) -> Result {
+    match (result, &claimant.error) {
+        (Ok(loaded), _) => loaded(world, claimant.target, &claimant.element),
+        (Err(e), Some(error)) => (error.0.lock().unwrap())(world, claimant.target, e)?,
+        (Err(e), None) => return Err(e),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,250 @@
+//! Mapping between the browser's URL and the app's location.
+
+use crate::js_err::JsErr;
+use bevy_ecs::prelude::*;
+
+/// Where the app's location is kept in the browser's URL.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub enum RouterMode {
+    /// The app's path is the URL's path, updated with the history API.
+    #[default]
+    History,
+    /// The app's path and query are kept in the URL's fragment, as in
+    /// `/index.html#/posts/12?tab=comments`.
+    ///
+    /// The server only ever sees requests for the page itself, so this suits
+    /// static hosts that can't route unknown paths to `index.html`.
+    Hash,
+}
+
+/// How the router reads and writes the browser's URL, as configured on
+/// [`RouterPlugin`](super::RouterPlugin).
+///
+/// Routes, [`Pathname`](super::Pathname), and
+/// [`Navigator::navigate`](super::Navigator::navigate) all deal in the app's
+/// location, which excludes the base path and, in [`RouterMode::Hash`], is
+/// taken from the fragment.
+#[derive(Resource, Clone, Default)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct RouterLocation {
+    mode: RouterMode,
+    /// Without a trailing slash, so the root is empty.
+    base: String,
+    pub(super) base_from_document: bool,
+}
+
+impl RouterLocation {
+    pub(super) fn new(mode: RouterMode, base: &str) -> Self {
+        let mut location = Self {
+            mode,
+            base: String::new(),
+            base_from_document: false,
+        };
+        location.set_base(base);
+        location
+    }
+
+    pub fn mode(&self) -> RouterMode {
+        self.mode
+    }
+
+    /// The path the app is mounted under, like `/tools/editor`. Empty at the
+    /// root.
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    pub(super) fn set_base(&mut self, base: &str) {
+        let base = base.trim_end_matches('/');
+        self.base = if base.is_empty() || base.starts_with('/') {
+            base.to_string()
+        } else {
+            format!("/{base}")
+        };
+    }
+
+    /// `path` relative to the base path, or `None` if it's outside it.
+    fn strip_base<'a>(&self, path: &'a str) -> Option<&'a str> {
+        match path.strip_prefix(self.base.as_str())? {
+            "" => Some("/"),
+            rest if rest.starts_with('/') => Some(rest),
+            _ => None,
+        }
+    }
+
+    /// The app's location within the browser's `href`.
+    ///
+    /// A path outside the base path is left as it is.
+    pub(super) fn app_url(&self, href: &str) -> Result<web_sys::Url> {
+        let url = web_sys::Url::new(href).js_err()?;
+
+        match self.mode {
+            RouterMode::History => {
+                let pathname = url.pathname();
+                if let Some(path) = self.strip_base(&pathname) {
+                    url.set_pathname(path);
+                }
+
+                Ok(url)
+            }
+            RouterMode::Hash => {
+                web_sys::Url::new_with_base(hash_location(&url.hash()), href).js_err()
+            }
+        }
+    }
+
+    /// Resolve `href`, written relative to the app, against the browser's
+    /// `current` href.
+    pub(super) fn resolve(&self, href: &str, current: &str) -> Result<web_sys::Url> {
+        let app = self.app_url(current)?;
+        let href = match self.mode {
+            RouterMode::History => href,
+            RouterMode::Hash => hash_relative(href)
+                .ok_or_else(|| format!("`{href}` is a fragment, not an app location"))?,
+        };
+
+        web_sys::Url::new_with_base(href, &app.href()).js_err()
+    }
+
+    /// Resolve an anchor's `href`, as the browser would, into an app location.
+    ///
+    /// Returns `None` for links outside the base path, which leave the app.
+    pub(super) fn resolve_link(&self, href: &str, current: &str) -> Result<Option<web_sys::Url>> {
+        match self.mode {
+            RouterMode::History => {
+                let url = web_sys::Url::new_with_base(href, current).js_err()?;
+                let pathname = url.pathname();
+                let Some(path) = self.strip_base(&pathname) else {
+                    return Ok(None);
+                };
+                url.set_pathname(path);
+
+                Ok(Some(url))
+            }
+            RouterMode::Hash => match hash_relative(href) {
+                Some(href) => self.resolve(href, current).map(Some),
+                None => Ok(None),
+            },
+        }
+    }
+
+    /// The browser href that shows the app location `url`, starting from the
+    /// browser's `current` href.
+    pub(super) fn browser_href(&self, url: &web_sys::Url, current: &str) -> Result<String> {
+        match self.mode {
+            RouterMode::History => {
+                let browser = web_sys::Url::new(&url.href()).js_err()?;
+                browser.set_pathname(&format!("{}{}", self.base, url.pathname()));
+
+                Ok(browser.href())
+            }
+            RouterMode::Hash => Ok(hash_href(
+                current,
+                &format!("{}{}", url.pathname(), url.search()),
+            )),
+        }
+    }
+}
+
+/// The app location a hash-mode fragment holds, like `/posts?tab=a` for
+/// `#/posts?tab=a`. Any other fragment shows the root.
+fn hash_location(hash: &str) -> &str {
+    hash.strip_prefix('#')
+        .filter(|path| path.starts_with('/'))
+        .unwrap_or("/")
+}
+
+/// An href written relative to the app in hash mode. Links may be written
+/// either as `/posts` or `#/posts`.
+///
+/// Returns `None` for other fragments, like `#section`, which point into the
+/// page and are left to the browser.
+fn hash_relative(href: &str) -> Option<&str> {
+    match href.strip_prefix('#') {
+        Some(path) if path.starts_with('/') => Some(path),
+        Some(_) => None,
+        None => Some(href),
+    }
+}
+
+/// The browser's `current` href, showing the app `location` instead.
+fn hash_href(current: &str, location: &str) -> String {
+    let page = current.split_once('#').map_or(current, |(page, _)| page);
+    format!("{page}#{location}")
+}
+
+/// Read the base path from the document's `<base href>`, if it has one.
+pub(super) fn document_base(window: &web_sys::Window) -> Result<Option<String>> {
+    let document = window
+        .document()
+        .ok_or("browser document should be available")?;
+    let Some(base) = document.query_selector("base[href]").js_err()? else {
+        return Ok(None);
+    };
+    let Some(href) = base.get_attribute("href") else {
+        return Ok(None);
+    };
+
+    let current = window.location().href().js_err()?;
+    let url = web_sys::Url::new_with_base(&href, &current).js_err()?;
+
+    Ok(Some(url.pathname()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_base_path() {
+        let location = RouterLocation::new(RouterMode::History, "tools/editor/");
+        assert_eq!(location.base(), "/tools/editor");
+
+        assert_eq!(location.strip_base("/tools/editor"), Some("/"));
+        assert_eq!(location.strip_base("/tools/editor/"), Some("/"));
+        assert_eq!(
+            location.strip_base("/tools/editor/posts/1"),
+            Some("/posts/1")
+        );
+        assert_eq!(location.strip_base("/tools/editorial"), None);
+        assert_eq!(location.strip_base("/about"), None);
+
+        let root = RouterLocation::new(RouterMode::History, "/");
+        assert_eq!(root.base(), "");
+        assert_eq!(root.strip_base("/about"), Some("/about"));
+    }
+
+    #[test]
+    fn test_hash_resolve() {
+        assert_eq!(
+            hash_location("#/posts/12?tab=comments"),
+            "/posts/12?tab=comments"
+        );
+        assert_eq!(hash_location(""), "/");
+        assert_eq!(hash_location("#section"), "/");
+
+        assert_eq!(hash_relative("/posts"), Some("/posts"));
+        assert_eq!(hash_relative("#/posts?tab=a"), Some("/posts?tab=a"));
+        assert_eq!(hash_relative("comments"), Some("comments"));
+        assert_eq!(hash_relative("?tab=a"), Some("?tab=a"));
+        assert_eq!(hash_relative("#section"), None);
+        assert_eq!(hash_relative("#"), None);
+    }
+
+    #[test]
+    fn test_hash_browser_href() {
+        assert_eq!(
+            hash_href("https://example.com/app/index.html", "/posts/12"),
+            "https://example.com/app/index.html#/posts/12"
+        );
+        assert_eq!(
+            hash_href("https://example.com/?v=2#/posts/12", "/about?tab=a"),
+            "https://example.com/?v=2#/about?tab=a"
+        );
+        assert_eq!(
+            hash_href("https://example.com/#section", "/"),
+            "https://example.com/#/"
+        );
+    }
+}
@@ -3,23 +3,90 @@ use crate::dom::{DomSystems, events::Events};
 use crate::js_err::JsErr;
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
-use bevy_ecs::system::{SystemId, SystemParam};
+use bevy_ecs::system::{RunSystemError, SystemId, SystemParam};
 use bevy_platform::collections::HashMap;
 use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::sync::{Arc, Mutex};
-use wasm_bindgen::JsValue;
 
+mod history;
+mod link;
+mod loader;
+mod location;
+pub mod params;
+mod prefetch;
 pub mod query;
+pub mod table;
+
+pub use history::{HistoryState, PreserveScroll};
+pub use link::ActiveLink;
+pub use loader::RouteData;
+pub use location::{RouterLocation, RouterMode};
+pub use params::{FromRouteParams, Params, ParamsError};
+pub use prefetch::PrefetchPolicy;
+pub use table::{RouteTableEntry, Sitemap, route_table};
 
 // TODO: okay this should probably be a lil entity set guy
 #[derive(Default)]
 #[cfg_attr(feature = "debug", derive(Debug))]
-pub struct RouterPlugin;
+pub struct RouterPlugin {
+    mode: RouterMode,
+    base: BasePath,
+    prefetch: Option<PrefetchPolicy>,
+}
+
+#[derive(Default)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+enum BasePath {
+    #[default]
+    Root,
+    Path(String),
+    Document,
+}
+
+impl RouterPlugin {
+    /// Choose where the app's location is kept in the browser's URL.
+    pub fn with_mode(mut self, mode: RouterMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Mount the app under `base`, like `/tools/editor`.
+    ///
+    /// Routes match, and [`Navigator::navigate`] takes, paths relative to the
+    /// base, while anchors are written with it and are only handled by the
+    /// router when they point inside it.
+    pub fn with_base(mut self, base: impl Into<String>) -> Self {
+        self.base = BasePath::Path(base.into());
+        self
+    }
+
+    /// Like [`Self::with_base`], but read the base from the document's
+    /// `<base href>` at startup.
+    pub fn with_document_base(mut self) -> Self {
+        self.base = BasePath::Document;
+        self
+    }
+
+    /// Prefetch route data when internal links are hovered or focused,
+    /// following `policy`.
+    pub fn with_prefetch(mut self, policy: PrefetchPolicy) -> Self {
+        self.prefetch = Some(policy);
+        self
+    }
+}
 
 impl Plugin for RouterPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(query::QueryPlugin)
+        let mut location = RouterLocation::new(self.mode, "");
+        match &self.base {
+            BasePath::Root => {}
+            BasePath::Path(base) => location.set_base(base),
+            BasePath::Document => location.base_from_document = true,
+        }
+
+        app.insert_resource(location)
+            .add_plugins(query::QueryPlugin)
             .add_systems(
                 PreStartup,
                 initialize_router.in_set(DomStartupSystems::Pathname),
@@ -33,13 +100,27 @@ impl Plugin for RouterPlugin {
                     hook_into_anchors
                         .after(DomSystems::Reparent)
                         .before(DomSystems::Attach),
+                    link::update_active_links
+                        .after(DomSystems::ResolveRoutes)
+                        .before(DomSystems::Insert),
+                    history::apply_scroll.after(DomSystems::Attach),
+                    guard_unload.after(DomSystems::ResolveRoutes),
                 ),
             )
             .init_resource::<RouteParams>()
             .init_resource::<NavigationGuard>()
+            .init_resource::<HistoryState>()
+            .init_resource::<history::ScrollPositions>()
+            .init_resource::<history::PendingScroll>()
+            .init_resource::<prefetch::PrefetchCache>()
             .add_observer(on_proceed)
             .add_observer(on_cancel)
-            .add_observer(open_new_tab);
+            .add_observer(open_new_tab)
+            .add_observer(link::remove_active_link);
+
+        if let Some(policy) = &self.prefetch {
+            app.insert_resource(policy.clone());
+        }
 
         #[cfg(all(debug_assertions, feature = "debug"))]
         app.add_systems(
@@ -74,39 +155,68 @@ impl Pathname {
 
 fn initialize_router(
     window: Single<(Entity, &Window)>,
+    mut location: ResMut<RouterLocation>,
     mut guard: ResMut<NavigationGuard>,
     mut commands: Commands,
 ) -> Result {
     let (window_entity, window) = window.into_inner();
 
-    commands.spawn((
-        EventOf(window_entity),
-        ev::pop_state(
+    if location.base_from_document
+        && let Some(base) = location::document_base(window)?
+    {
+        location.set_base(&base);
+    }
+
+    let listener = match location.mode() {
+        RouterMode::History => ev::pop_state(
             |_: Ev<web_sys::PopStateEvent>,
              window: Single<&Window>,
+             location: Res<RouterLocation>,
              mut commands: Commands|
-             -> Result {
-                let base = window.location().href().js_err()?;
-                let url = web_sys::Url::new(&base).js_err()?;
-                let new_href = url.href();
-                let new_path = url.pathname();
-
-                // The browser has already moved history; defer the decision to
-                // the guard, which either commits or parks it and re-pushes our
-                // prior location so the address bar stays honest.
-                commands.queue(move |world: &mut World| resolve_pop(world, new_href, new_path));
-
-                Ok(())
-            },
+             -> Result { history_moved(&window, &location, &mut commands) },
         ),
-    ));
+        // Traversing history between hashes still fires `popstate`, but only
+        // `hashchange` catches edits to the fragment in the address bar.
+        RouterMode::Hash => ev::hash_change(
+            |_: Ev<web_sys::HashChangeEvent>,
+             window: Single<&Window>,
+             location: Res<RouterLocation>,
+             mut commands: Commands|
+             -> Result { history_moved(&window, &location, &mut commands) },
+        ),
+    };
+    commands.spawn((EventOf(window_entity), listener));
 
-    let location = window.location();
-    let base = location.href().js_err()?;
-    let url = web_sys::Url::new(&base).js_err()?;
+    let base = window.location().href().js_err()?;
+    let url = location.app_url(&base)?;
     let pathname = url.pathname();
 
+    // The router restores scroll offsets itself, once the target route has
+    // rendered.
+    let history = window.history().js_err()?;
+    history
+        .set_scroll_restoration(web_sys::ScrollRestoration::Manual)
+        .js_err()?;
+
+    let entry = match history::HistoryEntry::current() {
+        // Reloaded or returned to from another document.
+        Some(entry) => {
+            let (x, y) = entry.scroll;
+            commands.insert_resource(history::PendingScroll(Some(
+                history::ScrollTarget::Position(x, y),
+            )));
+            entry
+        }
+        None => {
+            let entry = history::HistoryEntry::new(None);
+            history::replace_entry(None, &entry);
+            entry
+        }
+    };
+
     guard.current_href = base;
+    guard.current_key = entry.key;
+    commands.insert_resource(HistoryState(entry.state));
 
     commands.insert_resource(Pathname {
         pathname,
@@ -117,6 +227,20 @@ fn initialize_router(
     Ok(())
 }
 
+/// Handle the browser moving to a new location on its own, as with the back
+/// and forward buttons.
+fn history_moved(window: &Window, location: &RouterLocation, commands: &mut Commands) -> Result {
+    let new_href = window.location().href().js_err()?;
+    let new_path = location.app_url(&new_href)?.pathname();
+
+    // The browser has already moved history; defer the decision to
+    // the guard, which either commits or parks it and re-pushes our
+    // prior location so the address bar stays honest.
+    commands.queue(move |world: &mut World| resolve_pop(world, new_href, new_path));
+
+    Ok(())
+}
+
 #[derive(Component)]
 struct RouterLink;
 
@@ -132,31 +256,44 @@ fn hook_into_anchors(
         (With<A>, Changed<attr::Href>),
     >,
     events: Query<Entity, With<RouterLink>>,
+    preserve_scroll: Query<(), With<PreserveScroll>>,
     window: Single<&Window>,
+    location: Res<RouterLocation>,
+    prefetch: Option<Res<PrefetchPolicy>>,
     mut commands: Commands,
 ) -> Result {
-    let location = window.location();
-    let base = location.href().js_err()?;
+    let base = window.location().href().js_err()?;
 
     for (entity, href, handlers, has_download, target) in &anchors {
-        let absolute = web_sys::Url::new(href).is_ok();
-        if absolute || has_download || target.is_some_and(|t| t.as_ref() == "_blank") {
-            // if absolute, remove potential dangling handler
-            if let Some(handler) = handlers
-                .iter()
-                .flat_map(|h| h.iter())
-                .find_map(|h| events.get(h).ok())
-            {
-                commands.entity(handler).despawn();
-            }
+        // the href changed, so any handlers from before are stale
+        for handler in handlers
+            .iter()
+            .flat_map(|h| h.iter())
+            .filter_map(|h| events.get(h).ok())
+        {
+            commands.entity(handler).despawn();
+        }
 
-            // no need to intercept
+        let Some(url) = resolve_anchor(&location, &base, href, has_download, target)? else {
+            // absolute or outside the app, no need to intercept
             continue;
-        }
+        };
 
-        let url = web_sys::Url::new_with_base(href, &base).js_err()?;
-        let href = url.href();
         let path = url.pathname();
+        let href = location.browser_href(&url, &base)?;
+        let options = NavigateOptions {
+            preserve_scroll: preserve_scroll.contains(entity),
+            ..Default::default()
+        };
+
+        if prefetch.is_some() {
+            for handler in [
+                ev::pointer_enter(prefetch_handler(path.clone())),
+                ev::focus(prefetch_handler(path.clone())),
+            ] {
+                commands.spawn((RouterLink, EventOf(entity), handler));
+            }
+        }
 
         commands.spawn((
             RouterLink,
@@ -168,7 +305,7 @@ fn hook_into_anchors(
                     }
 
                     ev.prevent_default();
-                    request_push(&mut commands, href.clone(), path.clone());
+                    request_push(&mut commands, href.clone(), path.clone(), options.clone());
                 },
             ),
         ));
@@ -177,9 +314,35 @@ fn hook_into_anchors(
     Ok(())
 }
 
+/// Prefetch the routes at `path`, as a concise event handler.
+fn prefetch_handler(path: String) -> impl FnMut(Commands) + Send + Sync + 'static {
+    move |mut commands: Commands| {
+        let path = path.clone();
+        commands.queue(move |world: &mut World| prefetch::prefetch(world, &path));
+    }
+}
+
+/// Resolve an anchor's `href` into the app location it navigates to, or
+/// `None` if the browser should follow it itself.
+fn resolve_anchor(
+    location: &RouterLocation,
+    current: &str,
+    href: &str,
+    download: bool,
+    target: Option<&attr::Target>,
+) -> Result<Option<web_sys::Url>> {
+    let absolute = web_sys::Url::new(href).is_ok();
+    if absolute || download || target.is_some_and(|t| t.as_ref() == "_blank") {
+        return Ok(None);
+    }
+
+    location.resolve_link(href, current)
+}
+
 #[derive(SystemParam)]
 pub struct Navigator<'w, 's> {
     window: Single<'w, 's, &'static Window>,
+    location: Res<'w, RouterLocation>,
     commands: Commands<'w, 's>,
 }
 
@@ -190,8 +353,65 @@ impl std::fmt::Debug for Navigator<'_, '_> {
     }
 }
 
+/// Options for a single navigation.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct NavigateOptions {
+    /// Replace the current history entry instead of pushing a new one.
+    pub replace: bool,
+    /// A payload stored with the new history entry, read back through
+    /// [`HistoryState`] whenever the user returns to it. Set it with
+    /// [`with_state`](Self::with_state).
+    pub state: Option<serde_json::Value>,
+    /// Keep the page's scroll offset, rather than scrolling to the top or to
+    /// the element named by the URL's fragment.
+    pub preserve_scroll: bool,
+}
+
+impl NavigateOptions {
+    /// Store `state` with the new history entry. It's read back with
+    /// [`HistoryState::get`].
+    pub fn with_state<T: serde::Serialize>(mut self, state: &T) -> Result<Self> {
+        self.state = Some(serde_json::to_value(state)?);
+        Ok(self)
+    }
+}
+
 impl<'w, 's> Navigator<'w, 's> {
     pub fn navigate(&mut self, href: &str) -> Result<()> {
+        self.navigate_with(href, NavigateOptions::default())
+    }
+
+    /// Navigate to `href`, replacing the current history entry.
+    pub fn replace(&mut self, href: &str) -> Result<()> {
+        self.navigate_with(
+            href,
+            NavigateOptions {
+                replace: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Go back one entry in history, like the browser's back button.
+    pub fn back(&mut self) {
+        self.go(-1);
+    }
+
+    /// Go forward one entry in history, like the browser's forward button.
+    pub fn forward(&mut self) {
+        self.go(1);
+    }
+
+    /// Move `delta` entries through history. Zero does nothing, rather than
+    /// reloading the page.
+    pub fn go(&mut self, delta: i32) {
+        if delta != 0 {
+            request_traverse(&mut self.commands, delta);
+        }
+    }
+
+    pub fn navigate_with(&mut self, href: &str, options: NavigateOptions) -> Result<()> {
         // TODO: consider doing nothing when the urls are identical
         let base = self.window.location().href().js_err()?;
 
@@ -201,10 +421,11 @@ impl<'w, 's> Navigator<'w, 's> {
             return Ok(());
         }
 
-        let url = web_sys::Url::new_with_base(href, &base).js_err()?;
+        let url = self.location.resolve(href, &base)?;
+        let browser_href = self.location.browser_href(&url, &base)?;
         // Route through the guard rather than committing directly, so unsaved
         // work can veto or defer the navigation.
-        request_push(&mut self.commands, url.href(), url.pathname());
+        request_push(&mut self.commands, browser_href, url.pathname(), options);
 
         Ok(())
     }
@@ -222,23 +443,53 @@ pub struct NavigationGuard {
     /// `popstate` the browser has already discarded the prior location and
     /// [`Pathname`] only retains a bare pathname (no host/query).
     current_href: String,
+    /// The key of the history entry the user is currently on.
+    current_key: String,
+    /// The `beforeunload` handler, while a blocker vetoes navigation.
+    unload_listener: Option<Entity>,
+    /// When an app-initiated traversal the blockers already allowed was
+    /// started, so its `popstate` isn't vetoed a second time. `go` gives no
+    /// sign when there's nowhere to move to, so this only covers a `popstate`
+    /// arriving within [`TRAVERSE_TIMEOUT_MS`].
+    traversing: Option<f64>,
+}
+
+/// How long after [`Navigator::go`] a `popstate` is taken to be its traversal
+/// rather than the browser's own back or forward.
+const TRAVERSE_TIMEOUT_MS: f64 = 1000.0;
+
+/// Whether a `popstate` at `now` belongs to the traversal started at `started`.
+fn expected_traversal(started: Option<f64>, now: f64) -> bool {
+    started.is_some_and(|at| now - at <= TRAVERSE_TIMEOUT_MS)
 }
 
 /// A navigation intercepted and parked awaiting an app-level decision.
 #[derive(Clone)]
 #[cfg_attr(feature = "debug", derive(Debug))]
-struct NavigationIntent {
-    href: String,
-    path: String,
+enum NavigationIntent {
+    Push {
+        href: String,
+        path: String,
+        options: NavigateOptions,
+    },
+    /// A back/forward navigation from [`Navigator::go`], which hasn't moved
+    /// history yet.
+    Traverse(i32),
 }
 
 /// Fired when a registered blocker vetoes a navigation. The app resolves it by
 /// triggering [`NavigationProceed`] (commit) or [`NavigationCancel`] (stay).
 #[derive(Event, Clone)]
 #[cfg_attr(feature = "debug", derive(Debug))]
+#[non_exhaustive]
 pub struct NavigationBlocked {
+    /// The target, or empty for [`Self::delta`] traversals, as the browser
+    /// only reveals it once it gets there.
     pub href: String,
     pub path: String,
+    /// How many entries [`Navigator::go`] (or `back` and `forward`) would
+    /// move through history.
+    pub delta: Option<i32>,
 }
 
 /// Commit the parked navigation.
@@ -249,6 +500,38 @@ pub struct NavigationProceed;
 #[derive(Event)]
 pub struct NavigationCancel;
 
+/// Fired when a navigation is about to commit to a different path, before
+/// [`Pathname`] and the rest of the router's state are updated.
+#[derive(Event, Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct RouteWillChange {
+    pub from: String,
+    pub to: String,
+}
+
+/// Fired once the routes have been resolved for a new path, and the matched
+/// elements rendered. Paths that no route matches fire [`RouteNotFound`]
+/// instead.
+///
+/// `from` is `None` for the page's initial path.
+#[derive(Event, Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct RouteChanged {
+    pub from: Option<String>,
+    pub to: String,
+}
+
+/// Fired when a [`Route`] has nothing to render for the path. Its previous
+/// content stays in place.
+#[derive(Event, Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct RouteNotFound {
+    /// The `Route` entity.
+    pub route: Entity,
+    /// The part of the path left for this route, after any it's nested in.
+    pub path: String,
+}
+
 /// Open `url` in a new browser tab/window. The router observes this and calls
 /// `window.open(url, "_blank")`, centralizing the one raw platform call so
 /// callers just trigger the event instead of reaching for `web_sys` directly.
@@ -280,6 +563,54 @@ impl NavigationGuardExt for App {
     }
 }
 
+/// Have the browser confirm leaving the page while a blocker vetoes
+/// navigation, as it would otherwise be lost by closing the tab, reloading, or
+/// following an external link.
+///
+/// The blockers are re-checked each frame, and the listener is only attached
+/// while one vetoes, since any `beforeunload` listener keeps the page out of
+/// the browser's back/forward cache.
+fn guard_unload(world: &mut World) {
+    let needed = blocked(world);
+    let listener = world.resource::<NavigationGuard>().unload_listener;
+
+    match (needed, listener) {
+        (true, None) => {
+            let Ok(window) = world
+                .query_filtered::<Entity, With<Window>>()
+                .single(world)
+            else {
+                return;
+            };
+            let listener = world
+                .spawn((EventOf(window), ev::before_unload(confirm_unload)))
+                .id();
+            world
+                .resource_mut::<NavigationGuard>()
+                .bypass_change_detection()
+                .unload_listener = Some(listener);
+        }
+        (false, Some(listener)) => {
+            world
+                .resource_mut::<NavigationGuard>()
+                .bypass_change_detection()
+                .unload_listener = None;
+            if let Ok(listener) = world.get_entity_mut(listener) {
+                listener.despawn();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn confirm_unload(ev: Ev<web_sys::BeforeUnloadEvent>, world: &mut World) {
+    if blocked(world) {
+        ev.prevent_default();
+        // Required by older browsers.
+        ev.set_return_value("");
+    }
+}
+
 /// Run every registered blocker; `true` if any vetoes the navigation.
 fn blocked(world: &mut World) -> bool {
     let ids = world.resource::<NavigationGuard>().blockers.clone();
@@ -288,66 +619,177 @@ fn blocked(world: &mut World) -> bool {
 }
 
 /// Push a new history entry for `href` (used by anchor/`navigate` commits and
-/// when proceeding a parked intent).
-fn push_history(href: &str) {
-    if let Some(history) = web_sys::window().and_then(|w| w.history().ok()) {
-        let _ = history.push_state_with_url(&JsValue::NULL, "", Some(href));
+/// when proceeding a parked intent). This is the browser's full href, so in
+/// [`RouterMode::Hash`] it already carries the `#/path`.
+fn push_history(href: &str, entry: &history::HistoryEntry) {
+    history::push_entry(href, entry);
+}
+
+/// Record the window's scroll offset for the entry being left. When it's
+/// still the browser's current entry, it's also written into its state.
+fn remember_scroll(world: &mut World, still_current: bool) -> history::HistoryEntry {
+    let entry = history::HistoryEntry {
+        key: world.resource::<NavigationGuard>().current_key.clone(),
+        scroll: history::window_scroll(),
+        state: world.resource::<HistoryState>().0.clone(),
+    };
+
+    world
+        .resource_mut::<history::ScrollPositions>()
+        .0
+        .insert(entry.key.clone(), entry.scroll);
+    if still_current {
+        history::replace_entry(None, &entry);
     }
+
+    entry
 }
 
-/// Update the in-app router state (params + pathname + tracked href). Does not
-/// touch browser history.
-fn commit_state(world: &mut World, href: &str, path: &str) {
-    if let Ok(url) = web_sys::Url::new(href) {
+/// Update the in-app router state (params + pathname + tracked href + entry
+/// state). Does not touch browser history.
+fn commit_state(world: &mut World, href: &str, path: &str, state: Option<serde_json::Value>) {
+    let from = world.resource::<Pathname>().pathname().to_string();
+    if from != path {
+        world.trigger(RouteWillChange {
+            from,
+            to: path.to_string(),
+        });
+    }
+
+    if let Ok(url) = world.resource::<RouterLocation>().app_url(href) {
         world.resource_mut::<query::QueryParams>().update(&url);
     }
     world.resource_mut::<Pathname>().update(path.to_string());
     world.resource_mut::<NavigationGuard>().current_href = href.to_string();
+
+    let mut history_state = world.resource_mut::<HistoryState>();
+    history_state.set_if_neq(HistoryState(state));
 }
 
 /// Commit a push-style navigation (anchor / `navigate`, and proceeding a parked
-/// intent): a new history entry plus the router state update.
-fn commit_push(world: &mut World, href: &str, path: &str) {
-    push_history(href);
-    commit_state(world, href, path);
+/// intent): a new history entry, or a replaced one when
+/// [`NavigateOptions::replace`] is set, plus the router state update.
+fn commit_push(world: &mut World, href: &str, path: &str, options: &NavigateOptions) {
+    let entry = history::HistoryEntry::new(options.state.clone());
+    if options.replace {
+        history::replace_entry(Some(href), &entry);
+    } else {
+        remember_scroll(world, true);
+        push_history(href, &entry);
+    }
+    world.resource_mut::<NavigationGuard>().current_key = entry.key;
+
+    commit_state(world, href, path, entry.state);
+
+    if !options.preserve_scroll {
+        let target = match world.resource::<RouterLocation>().app_url(href) {
+            Ok(url) => history::ScrollTarget::for_url(&url),
+            Err(_) => history::ScrollTarget::Top,
+        };
+        world.resource_mut::<history::PendingScroll>().0 = Some(target);
+    }
 }
 
 /// Commit a clean back/forward: the browser already moved history, so only the
-/// router state is updated.
+/// router state is updated, and the entry's scroll offset is restored.
 fn commit_soft(world: &mut World, href: &str, path: &str) {
-    commit_state(world, href, path);
+    remember_scroll(world, false);
+
+    let (target, state) = match history::HistoryEntry::current() {
+        Some(entry) => {
+            let positions = &world.resource::<history::ScrollPositions>().0;
+            let (x, y) = positions.get(&entry.key).copied().unwrap_or(entry.scroll);
+            world.resource_mut::<NavigationGuard>().current_key = entry.key;
+            (history::ScrollTarget::Position(x, y), entry.state)
+        }
+        // An entry the router didn't write, like one from editing the URL's
+        // fragment by hand.
+        None => {
+            let entry = history::HistoryEntry::new(None);
+            history::replace_entry(None, &entry);
+            world.resource_mut::<NavigationGuard>().current_key = entry.key;
+            (history::ScrollTarget::Top, None)
+        }
+    };
+
+    commit_state(world, href, path, state);
+    world.resource_mut::<history::PendingScroll>().0 = Some(target);
 }
 
-/// Entry point for push-style navigations (anchor click, `Navigator::navigate`).
+/// Entry point for push-style navigations (anchor click, `Navigator::navigate`),
+/// including those that replace the current entry.
 /// Commits immediately when unblocked; otherwise parks the intent and fires
 /// [`NavigationBlocked`].
-fn request_push(commands: &mut Commands, href: String, path: String) {
+fn request_push(commands: &mut Commands, href: String, path: String, options: NavigateOptions) {
     commands.queue(move |world: &mut World| {
         if world.resource::<NavigationGuard>().pending.is_some() {
             // A decision is already in flight; coalesce.
             return;
         }
         if !blocked(world) {
-            commit_push(world, &href, &path);
+            commit_push(world, &href, &path, &options);
             return;
         }
-        world.resource_mut::<NavigationGuard>().pending = Some(NavigationIntent {
+        world.resource_mut::<NavigationGuard>().pending = Some(NavigationIntent::Push {
             href: href.clone(),
             path: path.clone(),
+            options,
+        });
+        world.trigger(NavigationBlocked {
+            href,
+            path,
+            delta: None,
         });
-        world.trigger(NavigationBlocked { href, path });
     });
 }
 
+/// Entry point for app-initiated back/forward navigations. Blockers are
+/// consulted before the browser moves, rather than undoing the move in
+/// [`resolve_pop`] as for the browser's own buttons.
+fn request_traverse(commands: &mut Commands, delta: i32) {
+    commands.queue(move |world: &mut World| {
+        if world.resource::<NavigationGuard>().pending.is_some() {
+            // A decision is already in flight; coalesce.
+            return;
+        }
+        if !blocked(world) {
+            traverse(world, delta);
+            return;
+        }
+        world.resource_mut::<NavigationGuard>().pending = Some(NavigationIntent::Traverse(delta));
+        world.trigger(NavigationBlocked {
+            href: String::new(),
+            path: String::new(),
+            delta: Some(delta),
+        });
+    });
+}
+
+/// Move `delta` entries through history, once the blockers have allowed it.
+fn traverse(world: &mut World, delta: i32) {
+    if let Some(history) = web_sys::window().and_then(|w| w.history().ok())
+        && history.go_with_delta(delta).is_ok()
+    {
+        world.resource_mut::<NavigationGuard>().traversing = Some(js_sys::Date::now());
+    }
+}
+
 /// Entry point for back/forward navigations. The browser has already moved, so
 /// when blocked we re-push the prior location to keep the address bar honest
 /// until the app decides.
 fn resolve_pop(world: &mut World, new_href: String, new_path: String) {
     let prior = world.resource::<NavigationGuard>().current_href.clone();
 
+    let started = world.resource_mut::<NavigationGuard>().traversing.take();
+    if expected_traversal(started, js_sys::Date::now()) {
+        // Started by the app, which already asked the blockers.
+        commit_soft(world, &new_href, &new_path);
+        return;
+    }
     if world.resource::<NavigationGuard>().pending.is_some() {
         // User pressed Back again while a decision is pending; keep them put.
-        push_history(&prior);
+        let entry = remember_scroll(world, false);
+        push_history(&prior, &entry);
         return;
     }
     if !blocked(world) {
@@ -355,35 +797,57 @@ fn resolve_pop(world: &mut World, new_href: String, new_path: String) {
         return;
     }
 
-    push_history(&prior);
-    world.resource_mut::<NavigationGuard>().pending = Some(NavigationIntent {
+    let entry = remember_scroll(world, false);
+    push_history(&prior, &entry);
+    world.resource_mut::<NavigationGuard>().pending = Some(NavigationIntent::Push {
         href: new_href.clone(),
         path: new_path.clone(),
+        options: NavigateOptions::default(),
     });
     world.trigger(NavigationBlocked {
         href: new_href,
         path: new_path,
+        delta: None,
     });
 }
 
-/// Commit the parked navigation. Always a push: a push intent never touched
-/// history, and a `Pop` intent's prior was re-pushed at block time, so the
-/// target must now be pushed. (A confirmed back thus becomes a forward push,
-/// with the caveat that the history stack gains a duplicate entry.)
+/// Commit the parked navigation. A vetoed browser back/forward is parked as a
+/// push: its prior was re-pushed at block time, so the target must now be
+/// pushed. (A confirmed back thus becomes a forward push, with the caveat that
+/// the history stack gains a duplicate entry.) An app-initiated traversal
+/// hasn't moved yet, so it's simply carried out.
 fn on_proceed(
     _: On<NavigationProceed>,
     mut guard: ResMut<NavigationGuard>,
     mut commands: Commands,
 ) {
     if let Some(intent) = guard.pending.take() {
-        commands.queue(move |world: &mut World| {
-            commit_push(world, &intent.href, &intent.path);
+        commands.queue(move |world: &mut World| match intent {
+            NavigationIntent::Push {
+                href,
+                path,
+                options,
+            } => commit_push(world, &href, &path, &options),
+            NavigationIntent::Traverse(delta) => traverse(world, delta),
         });
     }
 }
 
-fn on_cancel(_: On<NavigationCancel>, mut guard: ResMut<NavigationGuard>) {
+fn on_cancel(
+    _: On<NavigationCancel>,
+    mut guard: ResMut<NavigationGuard>,
+    location: Res<RouterLocation>,
+    query: Option<ResMut<query::QueryParams>>,
+) {
     guard.pending = None;
+
+    // A query change pushed through `QueryParams` is already in the resource,
+    // so it goes back to the URL that's staying.
+    if let Some(mut query) = query
+        && let Ok(url) = location.app_url(&guard.current_href)
+    {
+        query.set_if_neq(query::QueryParams::from_url(&url));
+    }
 }
 
 /// Open the triggered URL in a new browser tab. Unlike push navigations this
@@ -399,9 +863,85 @@ fn open_new_tab(ev: On<OpenNewTab>, window: Single<&Window>) {
 #[derive(Clone)]
 struct RouteElement(Arc<Mutex<dyn FnMut(&mut World, Entity) + Send + Sync>>);
 
-#[derive(Component, Default)]
+impl RouteElement {
+    fn new<F, B, M>(element: F) -> Self
+    where
+        F: IntoSystem<(), B, M>,
+        F::System: Send + Sync + 'static,
+        B: Bundle,
+    {
+        let mut system = IntoSystem::into_system(element);
+
+        Self(Arc::new(Mutex::new(
+            move |world: &mut World, entity: Entity| {
+                system.initialize(world);
+                let bundle = system.run((), world).unwrap();
+                world.entity_mut(entity).insert(bundle);
+            },
+        )))
+    }
+}
+
+#[derive(Clone)]
+struct GuardSystem(Arc<Mutex<dyn FnMut(&mut World) -> Result<RouteGuard> + Send + Sync>>);
+
+/// A route guard's decision, made before a matched route renders.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub enum RouteGuard {
+    /// Render the route.
+    Allow,
+    /// Skip the route, falling through to the next candidate.
+    Deny,
+    /// Navigate to this href instead, replacing the current history entry.
+    Redirect(String),
+}
+
+impl RouteGuard {
+    pub fn redirect(href: impl Into<String>) -> Self {
+        Self::Redirect(href.into())
+    }
+}
+
+#[derive(Component)]
 pub struct Route {
-    routes: Vec<(RouterPath, RouteElement)>,
+    routes: Vec<RouteEntry>,
+    keep_alive_limit: usize,
+}
+
+impl Default for Route {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A path pattern and the element it renders, along with any options set by
+/// the [`Route`] modifiers.
+struct RouteEntry {
+    path: RouterPath,
+    element: RouteElement,
+    /// Checked against the accumulated parameters before the route is
+    /// accepted. A route that fails falls through to the next candidate.
+    validate: Option<fn(&RouteParams) -> bool>,
+    /// Run in order before the route renders. The first that doesn't allow it
+    /// decides.
+    guards: Vec<GuardSystem>,
+    keep_alive: bool,
+    /// Render again when only the parameters change.
+    remount: bool,
+    /// Runs when the route matches. The element waits for its output.
+    loader: Option<loader::RouteLoader>,
+    /// Shown while the loader runs.
+    pending: Option<RouteElement>,
+    /// Shown if the loader fails.
+    error: Option<loader::RouteErrorElement>,
+}
+
+impl RouteEntry {
+    /// Whether a change of parameters alone renders the element again.
+    fn remounts(&self) -> bool {
+        self.remount || self.loader.is_some()
+    }
 }
 
 #[cfg(feature = "debug")]
@@ -410,15 +950,21 @@ impl std::fmt::Debug for Route {
         f.debug_struct("Route")
             .field(
                 "routes",
-                &self.routes.iter().map(|(p, _)| p).collect::<Vec<_>>(),
+                &self.routes.iter().map(|r| &r.path).collect::<Vec<_>>(),
             )
             .finish_non_exhaustive()
     }
 }
 
 impl Route {
+    /// How many inactive kept-alive routes a [`Route`] parks by default.
+    pub const DEFAULT_KEEP_ALIVE_LIMIT: usize = 8;
+
     pub const fn new() -> Self {
-        Self { routes: Vec::new() }
+        Self {
+            routes: Vec::new(),
+            keep_alive_limit: Self::DEFAULT_KEEP_ALIVE_LIMIT,
+        }
     }
 
     pub fn route<F, B, M>(mut self, route: &'static str, element: F) -> Self
@@ -428,24 +974,224 @@ impl Route {
         // F: Fn() -> B + Send + Sync + 'static,
         B: Bundle,
     {
-        let mut system = IntoSystem::into_system(element);
+        let element = RouteElement::new(element);
+        let path = RouterPath::from_static(route).expect("route string should be well-formed");
+
+        self.routes.push(RouteEntry {
+            path,
+            element,
+            validate: None,
+            guards: Vec::new(),
+            keep_alive: false,
+            remount: false,
+            loader: None,
+            pending: None,
+            error: None,
+        });
+        self
+    }
 
-        let element = RouteElement(Arc::new(Mutex::new(
-            move |world: &mut World, entity: Entity| {
+    /// Only match the previous route when its parameters can be read as `T`.
+    ///
+    /// Otherwise, resolution falls through to the next candidate, so
+    /// `/posts/:id` with `Post { id: u32 }` can sit beside `/posts/:slug`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no route has been added yet.
+    pub fn with_params<T: params::FromRouteParams>(mut self) -> Self {
+        self.last_route().validate = Some(params::validate::<T>);
+        self
+    }
+
+    /// Guard the previous route with a system deciding whether it may render.
+    ///
+    /// Guards run when the route matches, before its element is rendered, and
+    /// can read the matched [`RouteParams`]. A denied route falls through to
+    /// the next candidate, and a redirect replaces the current history entry.
+    ///
+    /// ```ignore
+    /// Route::new()
+    ///     .route("/admin", admin)
+    ///     .guard(|session: Res<Session>| match session.user {
+    ///         Some(_) => RouteGuard::Allow,
+    ///         None => RouteGuard::redirect("/login"),
+    ///     })
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if no route has been added yet.
+    pub fn guard<S, M>(mut self, guard: S) -> Self
+    where
+        S: IntoSystem<(), RouteGuard, M>,
+        S::System: Send + Sync + 'static,
+    {
+        let mut system = IntoSystem::into_system(guard);
+        let mut initialized = false;
+
+        let guard = GuardSystem(Arc::new(Mutex::new(move |world: &mut World| {
+            if !initialized {
                 system.initialize(world);
-                let bundle = system.run((), world).unwrap();
-                world.entity_mut(entity).insert(bundle);
-            },
-        )));
+                initialized = true;
+            }
+
+            system.run((), world).map_err(|e| match e {
+                RunSystemError::Failed(f) => f,
+                RunSystemError::Skipped(s) => s.into(),
+            })
+        })));
+
+        self.last_route().guards.push(guard);
+        self
+    }
+
+    /// Park the previous route's element when another route matches, rather
+    /// than despawning it, and show it again when this route matches.
+    ///
+    /// Its entities, their state, and its DOM nodes survive in between. The
+    /// element is rendered into its own entity placed right after the `Route`
+    /// entity, instead of onto the `Route` entity itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no route has been added yet.
+    pub fn keep_alive(mut self) -> Self {
+        self.last_route().keep_alive = true;
+        self
+    }
+
+    /// Render the previous route's element again when the path changes but
+    /// still matches it, as from `/posts/1` to `/posts/2`.
+    ///
+    /// By default, the element stays and only [`RouteParams`] change, so
+    /// systems can react with [`Params::is_changed`]. Routes with a
+    /// [loader](Self::loader) always remount, to load for the new parameters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no route has been added yet.
+    pub fn remount(mut self) -> Self {
+        self.last_route().remount = true;
+        self
+    }
+
+    /// Load data for the previous route before rendering its element.
+    ///
+    /// The loader is a system run each time the route matches, returning the
+    /// future to await. Its output is inserted as the [`RouteData`] resource
+    /// while the element renders, then kept as a component on the entity the
+    /// route rendered into. If the route stops matching first, the future is
+    /// dropped.
+    ///
+    /// ```ignore
+    /// Route::new()
+    ///     .route("/posts/:id", |post: Res<RouteData<Post>>| post_view(&post))
+    ///     .loader(|params: Params<PostId>| {
+    ///         let id = params.get().map(|p| p.id);
+    ///         async move { fetch_post(id?).await }
+    ///     })
+    ///     .pending(|| Text::new("Loading..."))
+    ///     .error(|In(error): In<BevyError>| Text::new(error.to_string()))
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if no route has been added yet.
+    pub fn loader<S, Fut, T, M>(mut self, loader: S) -> Self
+    where
+        S: IntoSystem<(), Fut, M>,
+        S::System: Send + Sync + 'static,
+        Fut: Future<Output = Result<T>> + 'static,
+        T: Send + Sync + 'static,
+    {
+        self.last_route().loader = Some(loader::RouteLoader::new(loader));
+        self
+    }
+
+    /// Render this in place of the previous route's element while its
+    /// [loader](Self::loader) runs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no route has been added yet.
+    pub fn pending<F, B, M>(mut self, element: F) -> Self
+    where
+        F: IntoSystem<(), B, M>,
+        F::System: Send + Sync + 'static,
+        B: Bundle,
+    {
+        self.last_route().pending = Some(RouteElement::new(element));
+        self
+    }
 
-        let route = RouterPath::from_static(route).expect("route string should be well-formed");
+    /// Render this, given the error, in place of the previous route's element
+    /// when its [loader](Self::loader) fails.
+    ///
+    /// Without one, the error goes to the app's error handler and the route
+    /// stays empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no route has been added yet.
+    pub fn error<F, B, M>(mut self, element: F) -> Self
+    where
+        F: IntoSystem<In<BevyError>, B, M>,
+        F::System: Send + Sync + 'static,
+        B: Bundle,
+    {
+        self.last_route().error = Some(loader::RouteErrorElement::new(element));
+        self
+    }
 
-        self.routes.push((route, element));
+    /// Set how many inactive kept-alive routes stay parked. Past this, the
+    /// least recently shown are despawned.
+    pub fn keep_alive_limit(mut self, limit: usize) -> Self {
+        self.keep_alive_limit = limit;
         self
     }
+
+    fn last_route(&mut self) -> &mut RouteEntry {
+        self.routes
+            .last_mut()
+            .expect("route modifiers must follow a call to `Route::route`")
+    }
 }
 
-#[derive(Resource, Default)]
+/// The element of a kept-alive route, rendered for the `Route` entity this
+/// points to.
+///
+/// While its route is active, a view is a sibling of the `Route` entity.
+/// Otherwise, it has no parent and its nodes are detached from the document.
+#[derive(Component)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[relationship(relationship_target = RouteViews)]
+pub struct RouteViewOf(pub Entity);
+
+/// The kept-alive views of a `Route` entity, despawned along with it.
+#[derive(Component)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[relationship_target(relationship = RouteViewOf, linked_spawn)]
+pub struct RouteViews(Vec<Entity>);
+
+/// Which route a view was rendered for, and when it was last shown.
+#[derive(Component)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+struct KeptAlive {
+    index: usize,
+    last_active: u32,
+}
+
+impl KeptAlive {
+    fn new(index: usize) -> Self {
+        Self {
+            index,
+            last_active: 0,
+        }
+    }
+}
+
+#[derive(Resource, Default, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "debug", derive(Debug))]
 #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
 #[cfg_attr(feature = "reflect", reflect(Resource))]
@@ -455,19 +1201,21 @@ impl RouteParams {
     pub fn get(&self, param: &str) -> Option<&str> {
         self.0.get(param).map(|s| s.as_str())
     }
-
-    fn clear(&mut self) {
-        self.0.clear();
-    }
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 #[cfg_attr(any(test, feature = "debug"), derive(Debug))]
 #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
 pub enum PathSegment {
     Root,
     Static(Cow<'static, str>),
+    /// `:name`, matching any single segment.
     Param(Cow<'static, str>),
+    /// `:name?`, matching a single segment if there is one.
+    Optional(Cow<'static, str>),
+    /// `*name`, matching the rest of the path, including nothing. A bare `*`
+    /// matches without capturing.
+    CatchAll(Cow<'static, str>),
 }
 
 #[derive(Debug)]
@@ -475,14 +1223,37 @@ pub struct PathSegmentError;
 
 impl PathSegment {
     fn from_static(segment: &'static str) -> Self {
+        if let Some(param) = segment.strip_prefix('*') {
+            return Self::CatchAll(Cow::Borrowed(param));
+        }
+
         match segment.strip_prefix(':') {
-            Some(param) => Self::Param(Cow::Borrowed(param)),
+            Some(param) => match param.strip_suffix('?') {
+                Some(param) => Self::Optional(Cow::Borrowed(param)),
+                None => Self::Param(Cow::Borrowed(param)),
+            },
             None => Self::Static(Cow::Borrowed(segment)),
         }
     }
+
+    /// How strongly this segment constrains a match, from most to least:
+    /// static, dynamic, optional, catch-all.
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Root | Self::Static(_) => 3,
+            Self::Param(_) => 2,
+            Self::Optional(_) => 1,
+            Self::CatchAll(_) => 0,
+        }
+    }
+
+    /// Whether this segment can match without consuming any input.
+    fn is_flexible(&self) -> bool {
+        matches!(self, Self::Optional(_) | Self::CatchAll(_))
+    }
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 #[cfg_attr(any(test, feature = "debug"), derive(Debug))]
 #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
 pub struct RouterPath(Vec<PathSegment>);
@@ -497,18 +1268,32 @@ impl RouterPath {
             return Ok(Self(vec![PathSegment::Root]));
         }
 
-        Ok(Self(
-            path.split('/')
-                .filter(|segment| !segment.is_empty())
-                .map(PathSegment::from_static)
-                .collect(),
-        ))
+        let segments: Vec<_> = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(PathSegment::from_static)
+            .collect();
+
+        // Nothing can follow a segment that takes the rest of the path.
+        let catch_all = segments
+            .iter()
+            .position(|s| matches!(s, PathSegment::CatchAll(_)));
+        if catch_all.is_some_and(|i| i + 1 != segments.len()) {
+            return Err(PathSegmentError);
+        }
+
+        Ok(Self(segments))
     }
 
     fn len(&self) -> usize {
         self.0.len()
     }
 
+    /// The number of segments that must be present to match.
+    fn required_len(&self) -> usize {
+        self.0.iter().filter(|s| !s.is_flexible()).count()
+    }
+
     fn is_root(&self) -> bool {
         self.0.len() == 1 && self.0[0] == PathSegment::Root
     }
@@ -529,6 +1314,25 @@ impl RouterPath {
         let mut tracked_split = TrackedSplit::new(url);
 
         for segment in self.0.iter() {
+            match segment {
+                PathSegment::Optional(param_name) => {
+                    let mut lookahead = tracked_split.clone();
+                    if let Some(input) = lookahead.next().filter(|i| !i.is_empty()) {
+                        params.insert(param_name.to_string(), input.to_string());
+                        tracked_split = lookahead;
+                    }
+                    continue;
+                }
+                PathSegment::CatchAll(param_name) => {
+                    let rest = tracked_split.rest();
+                    if !param_name.is_empty() {
+                        params.insert(param_name.to_string(), rest.to_string());
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
             let Some(input) = tracked_split.next() else {
                 return Err("unexpected path end".into());
             };
@@ -543,6 +1347,7 @@ impl RouterPath {
                 PathSegment::Param(param_name) => {
                     params.insert(param_name.to_string(), input.to_string());
                 }
+                PathSegment::Optional(_) | PathSegment::CatchAll(_) => unreachable!(),
             }
         }
 
@@ -554,25 +1359,25 @@ impl RouterPath {
     }
 
     /// Compare the specificity of two path patterns.
+    ///
+    /// Patterns requiring more segments are more specific. Otherwise, the
+    /// first differing segment decides: static beats dynamic, dynamic beats
+    /// optional, and optional beats catch-all. Failing that, the pattern with
+    /// fewer flexible segments wins.
     fn cmp_specificity(&self, other: &Self) -> Ordering {
-        match self.len().cmp(&other.len()) {
+        match self.required_len().cmp(&other.required_len()) {
             Ordering::Equal => {}
             other => return other,
         }
 
-        for pair in self.0.iter().zip(&other.0) {
-            match pair {
-                (PathSegment::Static(_), PathSegment::Param(_)) => {
-                    return Ordering::Greater;
-                }
-                (PathSegment::Param(_), PathSegment::Static(_)) => {
-                    return Ordering::Less;
-                }
-                _ => {}
+        for (a, b) in self.0.iter().zip(&other.0) {
+            match a.rank().cmp(&b.rank()) {
+                Ordering::Equal => {}
+                other => return other,
             }
         }
 
-        Ordering::Equal
+        other.len().cmp(&self.len())
     }
 
     // pub fn compare(&self, other: &Self, path: &str) -> core::cmp::Ordering {
@@ -598,105 +1403,507 @@ struct RouteParseResult<'a> {
 #[cfg_attr(feature = "debug", derive(Debug))]
 #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
 #[cfg_attr(feature = "reflect", reflect(Component))]
-pub struct MatchedRoute(String);
+pub struct MatchedRoute {
+    index: usize,
+    path: String,
+}
 
-fn resolve_routes(
-    body: Query<Entity, With<Body>>,
-    nodes: Query<(Option<&Route>, Option<&MatchedRoute>, Option<&Children>)>,
-    pathname: Res<Pathname>,
-    mut route_params: ResMut<RouteParams>,
-    mut commands: Commands,
-) -> Result {
-    fn find_routes(
-        nodes: &Query<(Option<&Route>, Option<&MatchedRoute>, Option<&Children>)>,
-        parent_entity: Entity,
-        path: &mut &str,
-        route_params: &mut RouteParams,
-        commands: &mut Commands,
-    ) -> Result {
-        let (.., children) = nodes.get(parent_entity)?;
-
-        for child_entity in children.iter().flat_map(|c| c.iter()) {
-            let Ok((child_route, matched_route, ..)) = nodes.get(child_entity) else {
+impl MatchedRoute {
+    /// The position of the showing route in its [`Route`], in the order the
+    /// routes were added.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The part of the path it matched.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+fn resolve_routes(world: &mut World) -> Result {
+    if resolve_current(world)? == Resolution::Matched {
+        let pathname = world.resource::<Pathname>();
+        let event = RouteChanged {
+            from: pathname.previous_path.clone(),
+            to: pathname.pathname.clone(),
+        };
+        world.trigger(event);
+    }
+
+    Ok(())
+}
+
+/// How resolving the routes for a path went.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+enum Resolution {
+    /// At least one route matched.
+    Matched,
+    /// No route matched, and each fired [`RouteNotFound`].
+    NotFound,
+    /// A guard redirected elsewhere instead.
+    Redirected,
+}
+
+/// Resolve the routes for the current path.
+fn resolve_current(world: &mut World) -> Result<Resolution> {
+    let path = world.resource::<Pathname>().pathname.clone();
+    let body = world
+        .query_filtered::<Entity, With<Body>>()
+        .single(world)?;
+
+    resolve_from(world, body, &path, RouteParams::default())
+}
+
+/// Resolve only the routes among `parent`'s descendants, given `path`, the
+/// part of the current path left for them.
+fn resolve_under(world: &mut World, parent: Entity, path: &str) -> Result<Resolution> {
+    // The parameters matched elsewhere still hold.
+    let params = world.resource::<RouteParams>().clone();
+    resolve_from(world, parent, path, params)
+}
+
+fn resolve_from(
+    world: &mut World,
+    parent: Entity,
+    mut path: &str,
+    mut params: RouteParams,
+) -> Result<Resolution> {
+    // Guards and elements see the parameters as they're matched, but only
+    // the final change counts, so systems only see one when the parameters
+    // actually differ.
+    let previous = core::mem::take(
+        world
+            .resource_mut::<RouteParams>()
+            .bypass_change_detection(),
+    );
+
+    let mut matched = false;
+    let redirect = find_routes(world, parent, &mut path, &mut params, &mut matched);
+
+    let mut current = world.resource_mut::<RouteParams>();
+    *current.bypass_change_detection() = previous;
+
+    match redirect? {
+        Some(href) => {
+            follow_redirect(world, &href)?;
+            Ok(Resolution::Redirected)
+        }
+        None => {
+            current.set_if_neq(params);
+            Ok(match matched {
+                true => Resolution::Matched,
+                false => Resolution::NotFound,
+            })
+        }
+    }
+}
+
+/// Resolve the routes among `parent`'s descendants, in order, each consuming
+/// the part of `path` it matches and setting `matched`. Stops at a guard's
+/// redirect and returns it.
+///
+/// Matched routes render immediately, so routes nested in a newly rendered
+/// element resolve in the same pass.
+fn find_routes(
+    world: &mut World,
+    parent: Entity,
+    path: &mut &str,
+    params: &mut RouteParams,
+    matched: &mut bool,
+) -> Result<Option<String>> {
+    // Re-read on each step, since rendering a kept-alive route moves views in
+    // and out right after it.
+    let mut index = 0;
+    while let Some(child) = world
+        .get::<Children>(parent)
+        .and_then(|c| c.iter().nth(index))
+    {
+        index += 1;
+
+        if world.get::<Route>(child).is_some()
+            && let Some(redirect) = match_route(world, child, path, params, matched)?
+        {
+            return Ok(Some(redirect));
+        }
+
+        if let Some(redirect) = find_routes(world, child, path, params, matched)? {
+            return Ok(Some(redirect));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Match `path` against the routes of `entity`, rendering the first candidate
+/// its guards allow unless it's already showing. A route showing with other
+/// parameters only rechecks its guards, unless it remounts.
+fn match_route(
+    world: &mut World,
+    entity: Entity,
+    path: &mut &str,
+    params: &mut RouteParams,
+    matched: &mut bool,
+) -> Result<Option<String>> {
+    let Some(route) = world.get::<Route>(entity) else {
+        return Ok(None);
+    };
+    let showing = world
+        .get::<MatchedRoute>(entity)
+        .map(|m| (m.index, m.path.clone()));
+
+    let candidates = route_candidates(route, path, params)
+        .into_iter()
+        .map(|(index, parse_result, candidate)| {
+            let entry = &route.routes[index];
+            let guards = entry.guards.clone();
+            (index, parse_result, candidate, guards, entry.remounts())
+        })
+        .collect::<Vec<_>>();
+
+    for (index, parse_result, candidate, guards, remounts) in candidates {
+        let same_route = showing.as_ref().filter(|(i, _)| *i == index);
+        if same_route.is_some_and(|(_, m)| m == parse_result.matched) {
+            *params = candidate;
+            *path = parse_result.remainder;
+            *matched = true;
+            return Ok(None);
+        }
+
+        // Guards and the element see the parameters they matched with.
+        *world
+            .resource_mut::<RouteParams>()
+            .bypass_change_detection() = candidate.clone();
+
+        match run_guards(world, &guards)? {
+            RouteGuard::Allow => {}
+            RouteGuard::Deny => continue,
+            RouteGuard::Redirect(href) => return Ok(Some(href)),
+        }
+
+        // Only the parameters changed, so the element stays.
+        if same_route.is_some() && !remounts {
+            let mut matched_route = world
+                .get_mut::<MatchedRoute>(entity)
+                .ok_or("a showing route should have a `MatchedRoute`")?;
+            matched_route.path = parse_result.matched.to_string();
+        } else {
+            render_route(
+                world,
+                entity,
+                index,
+                parse_result.matched.to_string(),
+                parse_result.remainder,
+            )?;
+        }
+
+        *params = candidate;
+        *path = parse_result.remainder;
+        *matched = true;
+        return Ok(None);
+    }
+
+    world.trigger(RouteNotFound {
+        route: entity,
+        path: path.to_string(),
+    });
+
+    Ok(None)
+}
+
+/// The routes of `route` that match `path` and accept their parameters, most
+/// specific first, with the parameters accumulated so far extended by theirs.
+fn route_candidates<'a>(
+    route: &Route,
+    path: &'a str,
+    params: &RouteParams,
+) -> Vec<(usize, RouteParseResult<'a>, RouteParams)> {
+    let mut routes = route.routes.iter().enumerate().collect::<Vec<_>>();
+    routes.sort_by(|a, b| a.1.path.cmp_specificity(&b.1.path).reverse());
+
+    routes
+        .into_iter()
+        .filter_map(|(index, route)| {
+            let parse_result = route.path.parse_path(path).ok()?;
+
+            let mut candidate = params.clone();
+            candidate.0.extend(parse_result.params.clone());
+            if route.validate.is_some_and(|validate| !validate(&candidate)) {
+                return None;
+            }
+
+            Some((index, parse_result, candidate))
+        })
+        .collect()
+}
+
+/// Find the routes among `parent`'s descendants that `path` would newly
+/// render, with the parameters each would see, without rendering them or
+/// running their guards.
+///
+/// Routes nested in those aren't rendered yet, so they can't be found.
+fn plan_routes(
+    world: &World,
+    parent: Entity,
+    path: &mut &str,
+    params: &mut RouteParams,
+    planned: &mut Vec<(Entity, usize, RouteParams)>,
+) {
+    let Some(children) = world.get::<Children>(parent) else {
+        return;
+    };
+
+    for child in children.iter() {
+        if let Some(route) = world.get::<Route>(child) {
+            let Some((index, parse_result, candidate)) =
+                route_candidates(route, path, params).into_iter().next()
+            else {
                 continue;
             };
 
-            if let Some(route) = child_route {
-                let mut routes = route.routes.iter().collect::<Vec<_>>();
-                routes.sort_unstable_by(|a, b| a.0.cmp_specificity(&b.0).reverse());
-
-                let mut best = routes.into_iter().filter_map(|(route, el)| {
-                    let parse_result = route.parse_path(path).ok()?;
-                    Some((el, parse_result))
-                });
-
-                match best.next() {
-                        Some((element, parse_result))
-                            // TODO: this isn't quite right
-                            if matched_route
-                                .is_none_or(|matched| matched.0 != parse_result.matched) =>
-                        {
-                            let inserter = element.clone();
-
-                            let RouteParseResult { matched, remainder, params } = parse_result;
-                            let matched = matched.to_string();
-
-                            route_params.0.extend(params);
-
-                            commands.queue(move |world: &mut World| -> Result {
-                                let components = world.components();
-                                let route_id = components.component_id::<Route>().unwrap();
-                                let child_id = components.component_id::<ChildOf>().unwrap();
-
-                                let mut entity = world.get_entity_mut(child_entity)?;
-                                let archetype = entity.archetype();
-                                let components: Vec<_> = archetype
-                                    .components()
-                                    .iter()
-                                    .copied()
-                                    .filter(|c| *c != route_id && *c != child_id)
-                                    .collect();
-
-                                entity.despawn_related::<Children>();
-                                entity.remove_by_ids(&components);
-
-                                (inserter.0.lock().unwrap())(world, child_entity);
-
-                                world
-                                    .entity_mut(child_entity)
-                                    .insert(MatchedRoute(matched));
-
-                                Ok(())
-                            });
-
-                            *path = remainder;
-                        }
-                        Some((_, parse_result)) => {
-                            *path = parse_result.remainder;
-                        }
-                        _ => {}
-                    }
+            let showing = world.get::<MatchedRoute>(child).is_some_and(|m| {
+                m.index == index
+                    && (m.path == parse_result.matched || !route.routes[index].remounts())
+            });
+
+            *path = parse_result.remainder;
+            *params = candidate.clone();
+
+            if !showing {
+                planned.push((child, index, candidate));
+                // Its content would be replaced.
+                continue;
             }
+        }
+
+        plan_routes(world, child, path, params, planned);
+    }
+}
 
-            find_routes(nodes, child_entity, path, route_params, commands)?;
+fn run_guards(world: &mut World, guards: &[GuardSystem]) -> Result<RouteGuard> {
+    for guard in guards {
+        match (guard.0.lock().unwrap())(world)? {
+            RouteGuard::Allow => {}
+            decision => return Ok(decision),
         }
+    }
 
-        Ok(())
+    Ok(RouteGuard::Allow)
+}
+
+/// Follow a guard's redirect, replacing the history entry that led to it.
+fn follow_redirect(world: &mut World, href: &str) -> Result {
+    let current = world.resource::<NavigationGuard>().current_href.clone();
+    let location = world.resource::<RouterLocation>();
+    let url = location.resolve(href, &current)?;
+    let browser_href = location.browser_href(&url, &current)?;
+    let path = url.pathname();
+
+    if path == world.resource::<Pathname>().pathname() {
+        return Err(format!("route guard redirected to the current path `{path}`").into());
     }
 
-    let path = pathname.pathname.clone();
-    let path = &mut path.as_str();
+    commit_push(
+        world,
+        &browser_href,
+        &path,
+        &NavigateOptions {
+            replace: true,
+            ..Default::default()
+        },
+    );
 
-    // // only run when the pathname changes
-    // if pathname.previous_path.as_deref() != Some(pathname.pathname.as_ref()) {
-    route_params.clear();
-    let body = body.single()?;
-    find_routes(&nodes, body, path, &mut route_params, &mut commands)?;
-    // }
+    Ok(())
+}
+
+/// Swap the route entity's content for the route at `index`, which matched
+/// `matched` and left `remainder` for the routes nested in it.
+fn render_route(
+    world: &mut World,
+    entity: Entity,
+    index: usize,
+    matched: String,
+    remainder: &str,
+) -> Result {
+    let route = world
+        .get::<Route>(entity)
+        .ok_or("route entity should have a `Route`")?;
+    let entry = route
+        .routes
+        .get(index)
+        .ok_or("matched route should still exist")?;
+    let element = entry.element.clone();
+    let loader = entry.loader.clone();
+    let pending = entry.pending.clone();
+    let error = entry.error.clone();
+    let keep_alive = entry.keep_alive;
+    let remount = entry.remounts();
+    let limit = route.keep_alive_limit;
+
+    let render = |world: &mut World, target: Entity| match &loader {
+        Some(loader) => {
+            let key = prefetch::PrefetchKey::new(entity, index, world.resource::<RouteParams>());
+            loader::start(
+                world,
+                target,
+                key,
+                loader,
+                pending.as_ref(),
+                element.clone(),
+                error.clone(),
+                remainder,
+            )
+        }
+        None => {
+            (element.0.lock().unwrap())(world, target);
+            Ok(())
+        }
+    };
+
+    let mut active = None;
+    let mut existing = None;
+    for view in world
+        .get::<RouteViews>(entity)
+        .iter()
+        .flat_map(|v| v.iter())
+    {
+        if world.get::<KeptAlive>(view).is_some_and(|k| k.index == index) {
+            existing = Some(view);
+        }
+        if world.get::<ChildOf>(view).is_some() {
+            active = Some(view);
+        }
+    }
+
+    // The route is showing already, and only its parameters changed.
+    if remount
+        && let Some(view) = existing
+        && active == existing
+    {
+        world.entity_mut(view).despawn();
+        existing = None;
+        active = None;
+    }
+
+    clear_rendered(world, entity)?;
+    world
+        .entity_mut(entity)
+        .insert(MatchedRoute { index, path: matched });
+
+    if let Some(active) = active
+        && !(keep_alive && existing == Some(active))
+    {
+        park_view(world, active);
+    }
+
+    if keep_alive {
+        let view = match existing {
+            Some(view) => view,
+            None => {
+                let view = world
+                    .spawn((RouteViewOf(entity), KeptAlive::new(index)))
+                    .id();
+                render(world, view)?;
+                view
+            }
+        };
+
+        if world.get::<ChildOf>(view).is_none() {
+            let parent = world
+                .get::<ChildOf>(entity)
+                .ok_or("a kept-alive route should have a parent")?
+                .parent();
+            let position = world
+                .get::<Children>(parent)
+                .and_then(|c| c.iter().position(|c| c == entity))
+                .map_or(0, |p| p + 1);
+            world.entity_mut(parent).insert_children(position, &[view]);
+        }
+
+        let tick = world.change_tick().get();
+        if let Some(mut kept_alive) = world.get_mut::<KeptAlive>(view) {
+            kept_alive.last_active = tick;
+        }
+
+        evict_views(world, entity, limit);
+    } else {
+        render(world, entity)?;
+    }
+
+    Ok(())
+}
+
+/// Remove everything a route rendered onto its own entity or a view.
+fn clear_rendered(world: &mut World, entity: Entity) -> Result {
+    let components = world.components();
+    let kept = [
+        components.component_id::<Route>(),
+        components.component_id::<ChildOf>(),
+        components.component_id::<RouteViews>(),
+        components.component_id::<RouteViewOf>(),
+        components.component_id::<KeptAlive>(),
+        components.component_id::<MatchedRoute>(),
+    ];
+
+    let mut entity = world.get_entity_mut(entity)?;
+    let archetype = entity.archetype();
+    let components: Vec<_> = archetype
+        .components()
+        .iter()
+        .copied()
+        .filter(|c| !kept.contains(&Some(*c)))
+        .collect();
+
+    entity.despawn_related::<Children>();
+    entity.remove_by_ids(&components);
 
     Ok(())
 }
 
+/// Take a kept-alive view out of the hierarchy and the document without
+/// despawning it.
+fn park_view(world: &mut World, view: Entity) {
+    detach_nodes(world, view);
+    world.entity_mut(view).remove::<ChildOf>();
+}
+
+/// Remove the topmost nodes under `entity` from the document, including its
+/// own. A route bundle without a root element only has its children's.
+fn detach_nodes(world: &World, entity: Entity) {
+    if let Some(node) = world.get::<crate::dom::html::Node>(entity) {
+        if let Some(parent) = node.parent_node() {
+            let _ = parent.remove_child(node);
+        }
+        return;
+    }
+
+    for child in world.get::<Children>(entity).iter().flat_map(|c| c.iter()) {
+        detach_nodes(world, child);
+    }
+}
+
+/// Despawn the least recently shown parked views beyond `limit`.
+fn evict_views(world: &mut World, route: Entity, limit: usize) {
+    let mut parked: Vec<_> = world
+        .get::<RouteViews>(route)
+        .iter()
+        .flat_map(|v| v.iter())
+        .filter(|v| world.get::<ChildOf>(*v).is_none())
+        .filter_map(|v| Some((world.get::<KeptAlive>(v)?.last_active, v)))
+        .collect();
+
+    if parked.len() <= limit {
+        return;
+    }
+
+    parked.sort_unstable();
+    for (_, view) in &parked[..parked.len() - limit] {
+        world.despawn(*view);
+    }
+}
+
+#[derive(Clone)]
 struct TrackedSplit<'a> {
     string: &'a str,
     start: usize,
@@ -725,6 +1932,14 @@ impl<'a> TrackedSplit<'a> {
             &self.string[self.start..]
         }
     }
+
+    /// Consume everything left, without its leading separator.
+    pub fn rest(&mut self) -> &'a str {
+        let rest = self.remainder();
+        self.start = self.string.len();
+
+        rest.strip_prefix('/').unwrap_or(rest)
+    }
 }
 
 impl<'a> Iterator for TrackedSplit<'a> {
@@ -809,4 +2024,494 @@ mod test {
 
         assert!(result.is_err());
     }
+
+    crate::route_params! {
+        struct Post {
+            id: u32,
+        }
+    }
+
+    #[derive(Component)]
+    struct ById;
+
+    #[derive(Component)]
+    struct BySlug;
+
+    pub(super) fn resolve(world: &mut World, path: &str) {
+        use bevy_ecs::system::RunSystemOnce;
+
+        world.insert_resource(Pathname {
+            previous_path: None,
+            pathname: path.into(),
+        });
+        let result: Result = world.run_system_once(resolve_routes).unwrap();
+        result.unwrap();
+    }
+
+    #[test]
+    fn test_typed_params() {
+        let mut params = RouteParams::default();
+        params.0.insert("id".into(), "12".into());
+
+        assert_eq!(Post::from_route_params(&params).unwrap().id, 12);
+
+        params.0.insert("id".into(), "twelve".into());
+        assert!(matches!(
+            Post::from_route_params(&params),
+            Err(ParamsError::Invalid { param: "id", .. })
+        ));
+
+        params.0.clear();
+        assert_eq!(
+            Post::from_route_params(&params).err(),
+            Some(ParamsError::Missing("id"))
+        );
+    }
+
+    #[test]
+    fn test_params_fall_through() {
+        let mut world = World::new();
+        world.init_resource::<RouteParams>();
+
+        let body = world.spawn(Body).id();
+        let route = world
+            .spawn((
+                ChildOf(body),
+                Route::new()
+                    .route("/posts/:id", || ById)
+                    .with_params::<Post>()
+                    .route("/posts/:slug", || BySlug),
+            ))
+            .id();
+
+        resolve(&mut world, "/posts/12");
+        assert!(world.entity(route).contains::<ById>());
+
+        resolve(&mut world, "/posts/hello");
+        assert!(world.entity(route).contains::<BySlug>());
+        assert!(!world.entity(route).contains::<ById>());
+    }
+
+    #[test]
+    fn test_route_events() {
+        #[derive(Resource, Default)]
+        struct Seen(Vec<String>);
+
+        let mut world = World::new();
+        world.init_resource::<RouteParams>();
+        world.init_resource::<Seen>();
+        world.add_observer(|ev: On<RouteChanged>, mut seen: ResMut<Seen>| {
+            seen.0.push(format!("changed {}", ev.to));
+        });
+        world.add_observer(|ev: On<RouteNotFound>, mut seen: ResMut<Seen>| {
+            seen.0.push(format!("not found {}", ev.path));
+        });
+
+        let body = world.spawn(Body).id();
+        world.spawn((ChildOf(body), Route::new().route("/posts/:id", || ById)));
+
+        resolve(&mut world, "/posts/1");
+        resolve(&mut world, "/about");
+        resolve(&mut world, "/posts/2");
+
+        assert_eq!(
+            world.resource::<Seen>().0,
+            ["changed /posts/1", "not found /about", "changed /posts/2"]
+        );
+    }
+
+    #[derive(Component)]
+    struct Tab(&'static str);
+
+    fn active_view(world: &World, route: Entity) -> Option<Entity> {
+        let views = world.get::<RouteViews>(route)?;
+        views.iter().find(|v| world.get::<ChildOf>(*v).is_some())
+    }
+
+    #[test]
+    fn test_params_change_keeps_element() {
+        let mut world = World::new();
+        world.init_resource::<RouteParams>();
+
+        let body = world.spawn(Body).id();
+        let route = world
+            .spawn((
+                ChildOf(body),
+                Route::new()
+                    .route("/posts/:id", || (ById, children![Inner]))
+                    .route("/tags/:tag", || (BySlug, children![Inner]))
+                    .remount(),
+            ))
+            .id();
+
+        resolve(&mut world, "/posts/1");
+        let child = world.get::<Children>(route).unwrap()[0];
+        let tick = world.increment_change_tick();
+
+        resolve(&mut world, "/posts/2");
+        assert_eq!(world.get::<Children>(route).unwrap()[..], [child]);
+        assert_eq!(world.get::<MatchedRoute>(route).unwrap().path(), "/posts/2");
+        assert_eq!(world.resource::<RouteParams>().get("id"), Some("2"));
+        assert!(
+            world
+                .resource_ref::<RouteParams>()
+                .last_changed()
+                .is_newer_than(tick, world.read_change_tick())
+        );
+
+        // Resolving the same parameters again isn't a change.
+        let tick = world.increment_change_tick();
+        resolve(&mut world, "/posts/2");
+        assert!(
+            !world
+                .resource_ref::<RouteParams>()
+                .last_changed()
+                .is_newer_than(tick, world.read_change_tick())
+        );
+
+        resolve(&mut world, "/tags/a");
+        let child = world.get::<Children>(route).unwrap()[0];
+        resolve(&mut world, "/tags/b");
+        assert!(world.get_entity(child).is_err());
+        assert!(world.entity(route).contains::<BySlug>());
+    }
+
+    #[test]
+    fn test_keep_alive() {
+        let mut world = World::new();
+        world.init_resource::<RouteParams>();
+
+        let body = world.spawn(Body).id();
+        let route = world
+            .spawn((
+                ChildOf(body),
+                Route::new()
+                    .route("/a", || (Tab("a"), children![Tab("a child")]))
+                    .keep_alive()
+                    .route("/b", || Tab("b"))
+                    .keep_alive()
+                    .route("/c", || Tab("c")),
+            ))
+            .id();
+
+        resolve(&mut world, "/a");
+        let a = active_view(&world, route).unwrap();
+        assert_eq!(world.get::<Tab>(a).unwrap().0, "a");
+        assert_eq!(world.get::<Children>(body).unwrap()[..], [route, a]);
+
+        resolve(&mut world, "/b");
+        let b = active_view(&world, route).unwrap();
+        assert_ne!(a, b);
+        assert!(world.get::<ChildOf>(a).is_none());
+        assert_eq!(world.get::<Children>(a).unwrap().len(), 1);
+
+        // Routes without keep-alive still render onto the route entity.
+        resolve(&mut world, "/c");
+        assert!(active_view(&world, route).is_none());
+        assert_eq!(world.get::<Tab>(route).unwrap().0, "c");
+
+        resolve(&mut world, "/a");
+        assert_eq!(active_view(&world, route), Some(a));
+        assert!(world.get::<Tab>(route).is_none());
+        assert_eq!(world.get::<Children>(body).unwrap()[..], [route, a]);
+    }
+
+    #[test]
+    fn test_keep_alive_limit() {
+        let mut world = World::new();
+        world.init_resource::<RouteParams>();
+
+        let body = world.spawn(Body).id();
+        let route = world
+            .spawn((
+                ChildOf(body),
+                Route::new()
+                    .keep_alive_limit(1)
+                    .route("/a", || Tab("a"))
+                    .keep_alive()
+                    .route("/b", || Tab("b"))
+                    .keep_alive()
+                    .route("/c", || Tab("c"))
+                    .keep_alive(),
+            ))
+            .id();
+
+        resolve(&mut world, "/a");
+        let a = active_view(&world, route).unwrap();
+        world.increment_change_tick();
+
+        resolve(&mut world, "/b");
+        let b = active_view(&world, route).unwrap();
+        world.increment_change_tick();
+
+        resolve(&mut world, "/c");
+        assert!(world.get_entity(a).is_err());
+        assert!(world.get_entity(b).is_ok());
+
+        world.despawn(route);
+        assert!(world.get_entity(b).is_err());
+    }
+
+    fn parse<'a>(pattern: &'static str, input: &'a str) -> Result<RouteParseResult<'a>> {
+        RouterPath::from_static(pattern).unwrap().parse_path(input)
+    }
+
+    fn params<const N: usize>(pairs: [(&str, &str); N]) -> HashMap<String, String> {
+        pairs
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_flexible_segments() {
+        let result = RouterPath::from_static("/posts/:id?/*rest").unwrap();
+
+        assert_eq!(
+            result,
+            RouterPath(vec![
+                PathSegment::Static("posts".into()),
+                PathSegment::Optional("id".into()),
+                PathSegment::CatchAll("rest".into()),
+            ])
+        );
+
+        assert!(RouterPath::from_static("/files/*path/edit").is_err());
+    }
+
+    #[test]
+    fn test_parse_optional() {
+        assert_eq!(
+            parse("/posts/:id?", "/posts/12/comments").unwrap(),
+            RouteParseResult {
+                matched: "/posts/12",
+                remainder: "/comments",
+                params: params([("id", "12")]),
+            }
+        );
+
+        assert_eq!(
+            parse("/posts/:id?", "/posts").unwrap(),
+            RouteParseResult {
+                matched: "/posts",
+                remainder: "",
+                params: params([]),
+            }
+        );
+
+        assert!(parse("/posts/:id?", "/users/12").is_err());
+    }
+
+    #[test]
+    fn test_parse_catch_all() {
+        assert_eq!(
+            parse("/files/*path", "/files/a/b.txt").unwrap(),
+            RouteParseResult {
+                matched: "/files/a/b.txt",
+                remainder: "",
+                params: params([("path", "a/b.txt")]),
+            }
+        );
+
+        assert_eq!(
+            parse("/files/*path", "/files").unwrap().params,
+            params([("path", "")])
+        );
+
+        assert_eq!(
+            parse("*", "/anything/at/all").unwrap(),
+            RouteParseResult {
+                matched: "/anything/at/all",
+                remainder: "",
+                params: params([]),
+            }
+        );
+
+        assert!(parse("*", "/").is_ok());
+    }
+
+    #[test]
+    fn test_specificity() {
+        let mut patterns = [
+            "*",
+            "/posts/:id?",
+            "/posts/*rest",
+            "/posts/:id",
+            "/posts/new",
+            "/posts",
+        ]
+        .map(|p| RouterPath::from_static(p).unwrap());
+        patterns.sort_by(|a, b| a.cmp_specificity(b).reverse());
+
+        let expected = [
+            "/posts/new",
+            "/posts/:id",
+            "/posts",
+            "/posts/:id?",
+            "/posts/*rest",
+            "*",
+        ]
+        .map(|p| RouterPath::from_static(p).unwrap());
+
+        assert_eq!(patterns, expected);
+    }
+
+    #[derive(Resource)]
+    struct LoggedIn(bool);
+
+    #[derive(Component)]
+    struct Admin;
+
+    #[derive(Component)]
+    struct Page;
+
+    #[test]
+    fn test_route_guards() {
+        let mut world = World::new();
+        world.init_resource::<RouteParams>();
+        world.insert_resource(LoggedIn(false));
+
+        let body = world.spawn(Body).id();
+        let route = world
+            .spawn((
+                ChildOf(body),
+                Route::new()
+                    .route("/admin", || Admin)
+                    .guard(|logged_in: Res<LoggedIn>| match logged_in.0 {
+                        true => RouteGuard::Allow,
+                        false => RouteGuard::Deny,
+                    })
+                    .route("/:page", || Page),
+            ))
+            .id();
+
+        resolve(&mut world, "/admin");
+        assert!(world.entity(route).contains::<Page>());
+        assert_eq!(world.resource::<RouteParams>().get("page"), Some("admin"));
+
+        world.resource_mut::<LoggedIn>().0 = true;
+        resolve(&mut world, "/home");
+        resolve(&mut world, "/admin");
+        assert!(world.entity(route).contains::<Admin>());
+        assert_eq!(world.resource::<RouteParams>().get("page"), None);
+    }
+
+    #[derive(Component)]
+    struct Outer;
+
+    #[derive(Component)]
+    struct Inner;
+
+    #[test]
+    fn test_nested_routes_resolve_in_one_pass() {
+        let mut world = World::new();
+        world.init_resource::<RouteParams>();
+
+        let body = world.spawn(Body).id();
+        let route = world
+            .spawn((
+                ChildOf(body),
+                Route::new().route("/outer", || {
+                    (
+                        Outer,
+                        children![Route::new().route("/inner/:id", || Inner)],
+                    )
+                }),
+            ))
+            .id();
+
+        resolve(&mut world, "/outer/inner/3");
+        assert!(world.entity(route).contains::<Outer>());
+
+        let nested = world.get::<Children>(route).unwrap()[0];
+        assert!(world.entity(nested).contains::<Inner>());
+        assert_eq!(world.resource::<RouteParams>().get("id"), Some("3"));
+    }
+
+    #[test]
+    fn test_plan_routes() {
+        let mut world = World::new();
+        world.init_resource::<RouteParams>();
+
+        let body = world.spawn(Body).id();
+        let route = world
+            .spawn((
+                ChildOf(body),
+                Route::new().route("/outer", || {
+                    (
+                        Outer,
+                        children![Route::new().route("/inner/:id", || Inner).remount()],
+                    )
+                }),
+            ))
+            .id();
+        resolve(&mut world, "/outer/inner/3");
+        let nested = world.get::<Children>(route).unwrap()[0];
+
+        let plan = |world: &World, mut path: &str| {
+            let mut planned = Vec::new();
+            plan_routes(world, body, &mut path, &mut RouteParams::default(), &mut planned);
+            planned
+                .into_iter()
+                .map(|(route, index, params)| (route, index, params.get("id").map(String::from)))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(plan(&world, "/outer/inner/3"), []);
+        assert_eq!(
+            plan(&world, "/outer/inner/4"),
+            [(nested, 0, Some("4".to_string()))]
+        );
+        // Nothing is rendered, so the old content is left alone.
+        assert!(world.entity(nested).contains::<Inner>());
+    }
+
+    #[test]
+    fn test_expected_traversal() {
+        assert!(expected_traversal(Some(1000.0), 1500.0));
+        // `go` had nowhere to move to, so this is the browser's own back.
+        assert!(!expected_traversal(Some(1000.0), 5000.0));
+        assert!(!expected_traversal(None, 1000.0));
+    }
+
+    #[test]
+    fn test_unload_guard() {
+        use wasm_bindgen::{JsCast, JsValue};
+
+        #[derive(Resource, Default)]
+        struct Dirty(bool);
+
+        let mut world = World::new();
+        world.init_resource::<NavigationGuard>();
+        world.init_resource::<Dirty>();
+        let window = world
+            .spawn(Window::new(JsValue::NULL.unchecked_into()))
+            .id();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(guard_unload);
+        let listener = |world: &World| world.resource::<NavigationGuard>().unload_listener;
+
+        let blocker = world.register_system(|dirty: Res<Dirty>| dirty.0);
+        world
+            .resource_mut::<NavigationGuard>()
+            .blockers
+            .push(blocker);
+        schedule.run(&mut world);
+        assert!(listener(&world).is_none());
+
+        // Added once a blocker vetoes, and only once.
+        world.resource_mut::<Dirty>().0 = true;
+        schedule.run(&mut world);
+        let added = listener(&world).unwrap();
+        assert_eq!(world.get::<EventOf>(added).unwrap().0, window);
+        schedule.run(&mut world);
+        assert_eq!(listener(&world), Some(added));
+
+        // Removed once none do.
+        world.resource_mut::<Dirty>().0 = false;
+        schedule.run(&mut world);
+        assert!(listener(&world).is_none());
+        assert!(world.get_entity(added).is_err());
+    }
 }
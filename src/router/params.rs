@@ -0,0 +1,148 @@
+//! Typed access to a route's dynamic segments.
+
+use super::RouteParams;
+use bevy_ecs::{prelude::*, system::SystemParam};
+use core::marker::PhantomData;
+use core::str::FromStr;
+
+/// A value built from the matched route's [`RouteParams`].
+///
+/// Implement this by hand, or declare the struct with [`route_params!`],
+/// which parses each field from the segment of the same name with
+/// [`FromStr`].
+///
+/// [`route_params!`]: crate::route_params
+pub trait FromRouteParams: Sized {
+    fn from_route_params(params: &RouteParams) -> Result<Self, ParamsError>;
+}
+
+/// Why the matched route's segments couldn't be read as the requested type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamsError {
+    /// No segment with this name was matched.
+    Missing(&'static str),
+    /// The segment was matched, but failed to parse.
+    Invalid {
+        param: &'static str,
+        value: String,
+        error: String,
+    },
+}
+
+impl core::fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Missing(param) => write!(f, "missing route parameter `{param}`"),
+            Self::Invalid {
+                param,
+                value,
+                error,
+            } => write!(f, "invalid route parameter `{param}` (\"{value}\"): {error}"),
+        }
+    }
+}
+
+impl core::error::Error for ParamsError {}
+
+impl RouteParams {
+    /// Parse the segment named `param`.
+    pub fn parse<T>(&self, param: &'static str) -> Result<T, ParamsError>
+    where
+        T: FromStr,
+        T::Err: core::fmt::Display,
+    {
+        let value = self.get(param).ok_or(ParamsError::Missing(param))?;
+
+        value.parse().map_err(|e: T::Err| ParamsError::Invalid {
+            param,
+            value: value.to_string(),
+            error: e.to_string(),
+        })
+    }
+}
+
+/// The current route's parameters, read as `T`.
+///
+/// ```ignore
+/// route_params! {
+///     struct Post { id: u32 }
+/// }
+///
+/// fn post(params: Params<Post>) -> impl Bundle {
+///     let text = match params.get() {
+///         Ok(Post { id }) => format!("Post {id}"),
+///         Err(e) => e.to_string(),
+///     };
+///
+///     (Div, children![Text::new(text)])
+/// }
+///
+/// Route::new().route("/posts/:id", post)
+/// ```
+#[derive(SystemParam)]
+pub struct Params<'w, T: FromRouteParams + 'static> {
+    params: Res<'w, RouteParams>,
+    marker: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "debug")]
+impl<T: FromRouteParams + 'static> std::fmt::Debug for Params<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Params")
+            .field("params", &*self.params)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: FromRouteParams + 'static> Params<'_, T> {
+    pub fn get(&self) -> Result<T, ParamsError> {
+        T::from_route_params(&self.params)
+    }
+
+    /// Whether the parameters changed since this system last ran.
+    pub fn is_changed(&self) -> bool {
+        self.params.is_changed()
+    }
+}
+
+/// Declare a struct that implements [`FromRouteParams`], parsing each field
+/// from the route segment of the same name.
+///
+/// ```ignore
+/// route_params! {
+///     pub struct Article {
+///         pub section: String,
+///         pub id: u64,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! route_params {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident: $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($(#[$field_meta])* $field_vis $field: $ty),*
+        }
+
+        impl $crate::router::params::FromRouteParams for $name {
+            fn from_route_params(
+                params: &$crate::router::RouteParams,
+            ) -> ::core::result::Result<Self, $crate::router::params::ParamsError> {
+                ::core::result::Result::Ok(Self {
+                    $($field: params.parse(stringify!($field))?),*
+                })
+            }
+        }
+    };
+}
+
+/// Used by [`Route::with_params`](super::Route::with_params) to reject
+/// candidates whose segments don't parse.
+pub(super) fn validate<T: FromRouteParams>(params: &RouteParams) -> bool {
+    T::from_route_params(params).is_ok()
+}
@@ -0,0 +1,352 @@
+//! Running route loaders ahead of navigation.
+
+use super::loader::{self, Claimant, Loaded};
+use super::{Body, Route, RouteParams, plan_routes};
+use crate::task::{TaskWorld, spawn_local};
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::HashMap;
+use send_wrapper::SendWrapper;
+use std::time::Duration;
+
+/// How links prefetch the data of the routes they point to, as enabled with
+/// [`RouterPlugin::with_prefetch`](super::RouterPlugin::with_prefetch).
+///
+/// Hovering or focusing an internal link runs the [loaders](super::Route::loader)
+/// of the routes it would newly render. If the link is followed, the result
+/// is used instead of loading again. Guards aren't consulted, and loaders see
+/// the target's [`RouteParams`], but otherwise the current world.
+#[derive(Resource, Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct PrefetchPolicy {
+    /// How long a prefetched result stays usable.
+    pub max_age: Duration,
+    /// How many prefetches may run at once. Links hovered past this don't
+    /// prefetch.
+    pub max_in_flight: usize,
+}
+
+impl Default for PrefetchPolicy {
+    fn default() -> Self {
+        Self {
+            max_age: Duration::from_secs(30),
+            max_in_flight: 4,
+        }
+    }
+}
+
+/// Identifies a load: the route, and the parameters its loader reads.
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub(super) struct PrefetchKey {
+    route: Entity,
+    index: usize,
+    params: Vec<(String, String)>,
+}
+
+impl PrefetchKey {
+    pub fn new(route: Entity, index: usize, params: &RouteParams) -> Self {
+        let mut params: Vec<_> = params
+            .0
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        params.sort_unstable();
+
+        Self {
+            route,
+            index,
+            params,
+        }
+    }
+}
+
+enum Entry {
+    /// Still running. A navigation that gets here first waits for it.
+    Loading(Option<Claimant>),
+    Ready {
+        loaded: SendWrapper<Loaded>,
+        /// In milliseconds since the epoch.
+        at: f64,
+    },
+}
+
+#[derive(Resource, Default)]
+pub(super) struct PrefetchCache {
+    entries: HashMap<PrefetchKey, Entry>,
+    in_flight: usize,
+}
+
+pub(super) enum Claimed {
+    /// The prefetch finished.
+    Ready(Loaded, Claimant),
+    /// The prefetch is running, and will hand its result to the claimant.
+    Waiting,
+    /// Nothing was prefetched.
+    Missed(Claimant),
+}
+
+fn now() -> f64 {
+    js_sys::Date::now()
+}
+
+fn expired(policy: &PrefetchPolicy, at: f64, now: f64) -> bool {
+    now - at > policy.max_age.as_secs_f64() * 1000.0
+}
+
+/// Take the prefetched result for `key`, or wait on it.
+pub(super) fn claim(world: &mut World, key: &PrefetchKey, claimant: Claimant) -> Claimed {
+    let Some(policy) = world.get_resource::<PrefetchPolicy>().cloned() else {
+        return Claimed::Missed(claimant);
+    };
+    let Some(mut cache) = world.get_resource_mut::<PrefetchCache>() else {
+        return Claimed::Missed(claimant);
+    };
+
+    match cache.entries.remove(key) {
+        Some(Entry::Ready { loaded, at }) if !expired(&policy, at, now()) => {
+            Claimed::Ready(loaded.take(), claimant)
+        }
+        Some(Entry::Loading(_)) => {
+            cache
+                .entries
+                .insert(key.clone(), Entry::Loading(Some(claimant)));
+            Claimed::Waiting
+        }
+        _ => Claimed::Missed(claimant),
+    }
+}
+
+/// Prefetch the loaders of the routes `path` would newly render.
+pub(super) fn prefetch(world: &mut World, path: &str) -> Result {
+    let Some(policy) = world.get_resource::<PrefetchPolicy>().cloned() else {
+        return Ok(());
+    };
+
+    let now = now();
+    world
+        .resource_mut::<PrefetchCache>()
+        .entries
+        .retain(|_, entry| match entry {
+            Entry::Ready { at, .. } => !expired(&policy, *at, now),
+            Entry::Loading(_) => true,
+        });
+
+    let body = world.query_filtered::<Entity, With<Body>>().single(world)?;
+    let mut path = path;
+    let mut targets = Vec::new();
+    plan_routes(
+        world,
+        body,
+        &mut path,
+        &mut RouteParams::default(),
+        &mut targets,
+    );
+
+    for (route, index, params) in targets {
+        let Some(loader) = world
+            .get::<Route>(route)
+            .and_then(|r| r.routes.get(index))
+            .and_then(|entry| entry.loader.clone())
+        else {
+            continue;
+        };
+
+        let key = PrefetchKey::new(route, index, &params);
+        let cache = world.resource::<PrefetchCache>();
+        if cache.entries.contains_key(&key) {
+            continue;
+        }
+        if cache.in_flight >= policy.max_in_flight {
+            break;
+        }
+
+        let mut current = world.resource_mut::<RouteParams>();
+        let saved = core::mem::replace(current.bypass_change_detection(), params);
+        let future = loader::run(&loader, world);
+        *world
+            .resource_mut::<RouteParams>()
+            .bypass_change_detection() = saved;
+        let future = future?;
+
+        let mut cache = world.resource_mut::<PrefetchCache>();
+        cache.in_flight += 1;
+        cache.entries.insert(key.clone(), Entry::Loading(None));
+
+        spawn_local(async move |mut world: TaskWorld| -> Result {
+            let result = future.await;
+            world.with(move |world| settle(world, key, result))
+        });
+    }
+
+    Ok(())
+}
+
+fn settle(world: &mut World, key: PrefetchKey, result: Result<Loaded>) -> Result {
+    let mut cache = world.resource_mut::<PrefetchCache>();
+    cache.in_flight = cache.in_flight.saturating_sub(1);
+    let entry = cache.entries.remove(&key);
+
+    match (entry, result) {
+        (Some(Entry::Loading(Some(claimant))), result) => loader::finish(world, claimant, result),
+        (Some(Entry::Loading(None)), Ok(loaded)) => {
+            world.resource_mut::<PrefetchCache>().entries.insert(
+                key,
+                Entry::Ready {
+                    loaded: SendWrapper::new(loaded),
+                    at: now(),
+                },
+            );
+            Ok(())
+        }
+        // Failures aren't kept, so following the link tries again.
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::router::{RouteData, clear_rendered, test::resolve};
+    use core::task::{Context, Poll, Waker};
+
+    #[derive(Component)]
+    struct Pending;
+
+    #[derive(Component)]
+    struct Post(u32);
+
+    #[derive(Component)]
+    struct Failed(String);
+
+    #[derive(Component)]
+    struct Comments;
+
+    /// Run the route's loader to completion, for the current parameters.
+    fn load(world: &mut World, route: Entity) -> Loaded {
+        let loader = world.get::<Route>(route).unwrap().routes[0]
+            .loader
+            .clone()
+            .unwrap();
+        let mut future = loader::run(&loader, world).unwrap();
+
+        match future
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()))
+        {
+            Poll::Ready(loaded) => loaded.unwrap(),
+            Poll::Pending => panic!("the loader should be ready"),
+        }
+    }
+
+    /// Have a prefetch for `/posts/{id}` in flight, so navigating there waits
+    /// on it rather than starting a load of its own.
+    fn hold(world: &mut World, route: Entity, id: &str) -> PrefetchKey {
+        let mut params = RouteParams::default();
+        params.0.insert("id".into(), id.into());
+        let key = PrefetchKey::new(route, 0, &params);
+        world
+            .resource_mut::<PrefetchCache>()
+            .entries
+            .insert(key.clone(), Entry::Loading(None));
+
+        key
+    }
+
+    #[test]
+    fn test_waiting_load() {
+        let mut world = World::new();
+        world.init_resource::<RouteParams>();
+        world.init_resource::<PrefetchPolicy>();
+        world.init_resource::<PrefetchCache>();
+
+        let body = world.spawn(Body).id();
+        let route = world
+            .spawn((
+                ChildOf(body),
+                Route::new()
+                    .route("/posts/:id", |post: Res<RouteData<u32>>| {
+                        (
+                            Post(**post),
+                            children![Route::new().route("/comments", || Comments)],
+                        )
+                    })
+                    .loader(|| async { Ok(7u32) })
+                    .pending(|| Pending)
+                    .error(|In(error): In<BevyError>| Failed(error.to_string())),
+            ))
+            .id();
+
+        let key = hold(&mut world, route, "1");
+        resolve(&mut world, "/posts/1/comments");
+        assert!(world.entity(route).contains::<Pending>());
+
+        let loaded = load(&mut world, route);
+        settle(&mut world, key, Ok(loaded)).unwrap();
+        assert!(!world.entity(route).contains::<Pending>());
+        assert_eq!(world.get::<Post>(route).unwrap().0, 7);
+        assert_eq!(world.get::<RouteData<u32>>(route).unwrap().0, 7);
+        assert!(!world.contains_resource::<RouteData<u32>>());
+
+        // Routes in the loaded element resolve against the rest of the path.
+        let nested = world.get::<Children>(route).unwrap()[0];
+        assert!(world.entity(nested).contains::<Comments>());
+
+        let key = hold(&mut world, route, "2");
+        resolve(&mut world, "/posts/2");
+        assert!(world.entity(route).contains::<Pending>());
+        assert!(!world.entity(route).contains::<RouteData<u32>>());
+
+        settle(&mut world, key, Err("offline".into())).unwrap();
+        assert!(!world.entity(route).contains::<Pending>());
+        assert!(!world.entity(route).contains::<Post>());
+        assert!(world.get::<Failed>(route).unwrap().0.contains("offline"));
+    }
+
+    #[test]
+    fn test_route_data_per_route() {
+        let mut world = World::new();
+        world.init_resource::<RouteParams>();
+        world.init_resource::<PrefetchPolicy>();
+        world.init_resource::<PrefetchCache>();
+
+        let body = world.spawn(Body).id();
+        let mut spawn_route = |path: &'static str, id: u32| {
+            world
+                .spawn((
+                    ChildOf(body),
+                    Route::new()
+                        .route(path, |post: Res<RouteData<u32>>| Post(**post))
+                        .loader(move || async move { Ok(id) }),
+                ))
+                .id()
+        };
+        let first = spawn_route("/first", 1);
+        let second = spawn_route("/second", 2);
+
+        let keys = [first, second].map(|route| {
+            let key = PrefetchKey::new(route, 0, &RouteParams::default());
+            world
+                .resource_mut::<PrefetchCache>()
+                .entries
+                .insert(key.clone(), Entry::Loading(None));
+            key
+        });
+        resolve(&mut world, "/first/second");
+
+        for (route, key) in [first, second].into_iter().zip(keys) {
+            let loaded = load(&mut world, route);
+            settle(&mut world, key, Ok(loaded)).unwrap();
+        }
+
+        // Routes loading the same type each keep their own.
+        assert_eq!(world.get::<Post>(first).unwrap().0, 1);
+        assert_eq!(world.get::<RouteData<u32>>(first).unwrap().0, 1);
+        assert_eq!(world.get::<Post>(second).unwrap().0, 2);
+        assert_eq!(world.get::<RouteData<u32>>(second).unwrap().0, 2);
+
+        // Clearing one route leaves the other's alone.
+        clear_rendered(&mut world, second).unwrap();
+        assert!(!world.entity(second).contains::<RouteData<u32>>());
+        assert_eq!(world.get::<RouteData<u32>>(first).unwrap().0, 1);
+    }
+}
@@ -1,9 +1,10 @@
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
-use bevy_platform::collections::HashMap;
+use core::str::FromStr;
 use wasm_bindgen::{JsCast, JsValue};
 use web_sys::UrlSearchParams;
 
+use super::{NavigateOptions, RouterLocation};
 use crate::{dom::DomSystems, js_err::JsErr, prelude::Window};
 
 #[derive(Default)]
@@ -12,7 +13,7 @@ pub struct QueryPlugin;
 
 impl Plugin for QueryPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.init_resource::<RouterLocation>().add_systems(
             PostUpdate,
             QueryParams::update_browser
                 .after(DomSystems::ResolveRoutes)
@@ -21,23 +22,40 @@ impl Plugin for QueryPlugin {
     }
 }
 
-#[derive(Resource, Default)]
+/// How a change to the query reaches the browser's history.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "debug"), derive(Debug))]
+pub enum QueryUpdate {
+    /// Rewrite the current history entry's URL.
+    #[default]
+    Replace,
+    /// Push a new history entry, so going back restores the previous query.
+    Push,
+}
+
+/// The current URL's query parameters, in order.
+///
+/// A key can repeat, as in `?tag=a&tag=b`. Changes are written back to the
+/// browser's URL.
+#[derive(Resource, Default, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "debug", derive(Debug))]
 #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
 #[cfg_attr(feature = "reflect", reflect(Resource))]
-pub struct QueryParams(HashMap<String, String>);
-
-impl core::ops::Deref for QueryParams {
-    type Target = HashMap<String, String>;
-
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
+pub struct QueryParams {
+    pairs: Vec<(String, String)>,
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    next_update: QueryUpdate,
 }
 
-impl core::ops::DerefMut for QueryParams {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for QueryParams {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            pairs: iter
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+            next_update: QueryUpdate::default(),
+        }
     }
 }
 
@@ -49,34 +67,512 @@ impl QueryParams {
     }
 
     pub(crate) fn update(&mut self, url: &web_sys::Url) {
-        self.clear();
+        self.pairs.clear();
 
         let js_params = url.search_params();
         for pair in js_params.entries() {
             let pair: js_sys::Array = pair.unwrap().unchecked_into();
             let key = pair.get(0).as_string().unwrap();
             let value = pair.get(1).as_string().unwrap();
-            self.insert(key, value);
+            self.pairs.push((key, value));
         }
     }
 
-    fn update_browser(window: Single<&Window>, params: Res<QueryParams>) -> Result {
-        let location = window.location();
-        let url = web_sys::Url::new(&location.href().js_err()?).js_err()?;
+    /// The first value of `key`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Every value of `key`, in order.
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.pairs
+            .iter()
+            .filter(move |(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.pairs.iter().any(|(k, _)| k == key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pairs.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Set `key` to a single value, replacing any it had. The value takes the
+    /// place of the first one.
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        let key = key.into();
+        let mut value = Some(value.into());
+
+        self.pairs.retain_mut(|(k, v)| {
+            if *k != key {
+                true
+            } else if let Some(value) = value.take() {
+                *v = value;
+                true
+            } else {
+                false
+            }
+        });
+
+        if let Some(value) = value {
+            self.pairs.push((key, value));
+        }
+    }
+
+    /// Add a value for `key`, after any it already has.
+    pub fn append(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.pairs.push((key.into(), value.into()));
+    }
+
+    /// Remove every value of `key`.
+    pub fn remove(&mut self, key: &str) {
+        self.pairs.retain(|(k, _)| k != key);
+    }
+
+    pub fn clear(&mut self) {
+        self.pairs.clear();
+    }
+
+    /// Choose how the next change reaches the browser's history. This resets
+    /// to [`QueryUpdate::Replace`] once written.
+    pub fn set_update(&mut self, update: QueryUpdate) {
+        self.next_update = update;
+    }
+
+    /// Read the parameters as `T`.
+    pub fn decode<T: FromQuery>(&self) -> Result<T, QueryError> {
+        T::from_query(self)
+    }
+
+    /// Write `value`'s fields, leaving other keys untouched.
+    pub fn encode<T: ToQuery>(&mut self, value: &T) {
+        value.to_query(self);
+    }
+
+    fn update_browser(
+        window: Single<&Window>,
+        location: Res<RouterLocation>,
+        mut params: ResMut<QueryParams>,
+        mut commands: Commands,
+    ) -> Result {
+        let href = window.location().href().js_err()?;
+        let url = location.app_url(&href)?;
 
         let js_params = UrlSearchParams::new().js_err()?;
         for (key, value) in params.iter() {
-            js_params.set(key, value);
+            js_params.append(key, value);
         }
 
         url.set_search(&String::from(js_params.to_string()));
 
-        window
-            .history()
-            .unwrap()
-            .replace_state_with_url(&JsValue::NULL, "", Some(&String::from(url.to_string())))
-            .unwrap();
+        let update = core::mem::take(&mut params.bypass_change_detection().next_update);
+        let browser_href = location.browser_href(&url, &href)?;
+        if browser_href == href {
+            return Ok(());
+        }
+
+        match update {
+            QueryUpdate::Replace => {
+                // Keep the router's entry state, like its scroll offset.
+                let history = window.history().js_err()?;
+                let state = history.state().unwrap_or(JsValue::NULL);
+                history
+                    .replace_state_with_url(&state, "", Some(&browser_href))
+                    .js_err()?;
+            }
+            QueryUpdate::Push => {
+                let options = NavigateOptions {
+                    preserve_scroll: true,
+                    ..Default::default()
+                };
+                super::request_push(&mut commands, browser_href, url.pathname(), options);
+            }
+        }
 
         Ok(())
     }
 }
+
+/// Why the query couldn't be read as the requested type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    /// A required key is absent.
+    Missing(&'static str),
+    /// A value failed to parse.
+    Invalid {
+        key: &'static str,
+        value: String,
+        error: String,
+    },
+}
+
+impl core::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Missing(key) => write!(f, "missing query parameter `{key}`"),
+            Self::Invalid { key, value, error } => {
+                write!(f, "invalid query parameter `{key}` (\"{value}\"): {error}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for QueryError {}
+
+/// A value decoded from [`QueryParams`].
+///
+/// Usually declared with [`query_params!`](crate::query_params).
+pub trait FromQuery: Sized {
+    fn from_query(query: &QueryParams) -> Result<Self, QueryError>;
+}
+
+/// A value encoded into [`QueryParams`].
+///
+/// Implementations should only touch their own keys, removing those they
+/// have no value for.
+pub trait ToQuery {
+    fn to_query(&self, query: &mut QueryParams);
+}
+
+/// A single query value.
+pub trait QueryValue: Sized {
+    fn parse_value(value: &str) -> Result<Self, String>;
+    fn format_value(&self) -> String;
+}
+
+macro_rules! impl_query_value {
+    ($($ty:ty),*) => {
+        $(
+            impl QueryValue for $ty {
+                fn parse_value(value: &str) -> Result<Self, String> {
+                    <$ty as FromStr>::from_str(value).map_err(|e| e.to_string())
+                }
+
+                fn format_value(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    };
+}
+
+impl_query_value!(
+    String, bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64
+);
+
+/// A field of a [`query_params!`](crate::query_params) struct, kept under
+/// one key.
+///
+/// A [`QueryValue`] is required, an `Option` may be absent, and a `Vec`
+/// takes every value of a repeated key.
+pub trait QueryField: Sized {
+    fn decode(key: &'static str, query: &QueryParams) -> Result<Self, QueryError>;
+    fn encode(&self, key: &str, query: &mut QueryParams);
+}
+
+fn parse_field<T: QueryValue>(key: &'static str, value: &str) -> Result<T, QueryError> {
+    T::parse_value(value).map_err(|error| QueryError::Invalid {
+        key,
+        value: value.to_string(),
+        error,
+    })
+}
+
+impl<T: QueryValue> QueryField for T {
+    fn decode(key: &'static str, query: &QueryParams) -> Result<Self, QueryError> {
+        parse_field(key, query.get(key).ok_or(QueryError::Missing(key))?)
+    }
+
+    fn encode(&self, key: &str, query: &mut QueryParams) {
+        query.set(key, self.format_value());
+    }
+}
+
+impl<T: QueryValue> QueryField for Option<T> {
+    fn decode(key: &'static str, query: &QueryParams) -> Result<Self, QueryError> {
+        query.get(key).map(|v| parse_field(key, v)).transpose()
+    }
+
+    fn encode(&self, key: &str, query: &mut QueryParams) {
+        match self {
+            Some(value) => query.set(key, value.format_value()),
+            None => query.remove(key),
+        }
+    }
+}
+
+impl<T: QueryValue> QueryField for Vec<T> {
+    fn decode(key: &'static str, query: &QueryParams) -> Result<Self, QueryError> {
+        query.get_all(key).map(|v| parse_field(key, v)).collect()
+    }
+
+    fn encode(&self, key: &str, query: &mut QueryParams) {
+        query.remove(key);
+        for value in self {
+            query.append(key, value.format_value());
+        }
+    }
+}
+
+/// Declare a struct that implements [`FromQuery`] and [`ToQuery`], keeping
+/// each field under the query key of the same name.
+///
+/// ```ignore
+/// query_params! {
+///     #[derive(Default)]
+///     pub struct Filters {
+///         pub tag: Vec<String>,
+///         pub page: Option<u32>,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! query_params {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident: $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($(#[$field_meta])* $field_vis $field: $ty),*
+        }
+
+        impl $crate::router::query::FromQuery for $name {
+            fn from_query(
+                query: &$crate::router::query::QueryParams,
+            ) -> ::core::result::Result<Self, $crate::router::query::QueryError> {
+                ::core::result::Result::Ok(Self {
+                    $($field: <$ty as $crate::router::query::QueryField>::decode(
+                        stringify!($field),
+                        query,
+                    )?),*
+                })
+            }
+        }
+
+        impl $crate::router::query::ToQuery for $name {
+            fn to_query(&self, query: &mut $crate::router::query::QueryParams) {
+                $($crate::router::query::QueryField::encode(
+                    &self.$field,
+                    stringify!($field),
+                    query,
+                );)*
+            }
+        }
+    };
+}
+
+/// The query read as `T`, registered with [`QueryViewExt::add_query_view`].
+///
+/// It follows [`QueryParams`], and changes made through it are written back,
+/// reaching the browser's history as chosen at registration. If the query
+/// doesn't decode, the view holds `T::default()` and [`error`](Self::error)
+/// says why.
+#[derive(Resource)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct QueryView<T> {
+    value: T,
+    error: Option<QueryError>,
+    update: QueryUpdate,
+    /// `value` as last encoded, to tell the app's changes from our own.
+    synced: Vec<(String, String)>,
+}
+
+impl<T> core::ops::Deref for QueryView<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> core::ops::DerefMut for QueryView<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<T: FromQuery + ToQuery + Default + Send + Sync + 'static> QueryView<T> {
+    fn new(update: QueryUpdate) -> Self {
+        Self {
+            value: T::default(),
+            error: None,
+            update,
+            synced: Vec::new(),
+        }
+    }
+
+    /// Why the current query couldn't be read as `T`, if it couldn't.
+    pub fn error(&self) -> Option<&QueryError> {
+        self.error.as_ref()
+    }
+
+    fn encoded(value: &T) -> Vec<(String, String)> {
+        let mut query = QueryParams::default();
+        value.to_query(&mut query);
+        query.pairs
+    }
+
+    fn sync(params: Res<QueryParams>, mut view: ResMut<Self>) {
+        let (value, error) = match params.decode::<T>() {
+            Ok(value) => (value, None),
+            Err(error) => (T::default(), Some(error)),
+        };
+
+        view.synced = Self::encoded(&value);
+        view.value = value;
+        view.error = error;
+    }
+
+    fn write_back(mut view: ResMut<Self>, mut params: ResMut<QueryParams>) {
+        let encoded = Self::encoded(&view.value);
+        if encoded == view.synced {
+            return;
+        }
+
+        let view = view.bypass_change_detection();
+        view.synced = encoded;
+        view.error = None;
+
+        params.encode(&view.value);
+        params.set_update(view.update);
+    }
+}
+
+/// Register a typed [`QueryView`].
+pub trait QueryViewExt {
+    /// Keep a [`QueryView<T>`] in step with the query, writing the app's
+    /// changes to it back with `update`.
+    fn add_query_view<T>(&mut self, update: QueryUpdate) -> &mut Self
+    where
+        T: FromQuery + ToQuery + Default + Send + Sync + 'static;
+}
+
+impl QueryViewExt for App {
+    fn add_query_view<T>(&mut self, update: QueryUpdate) -> &mut Self
+    where
+        T: FromQuery + ToQuery + Default + Send + Sync + 'static,
+    {
+        self.insert_resource(QueryView::<T>::new(update))
+            .add_systems(
+                PreUpdate,
+                QueryView::<T>::sync.run_if(resource_changed::<QueryParams>),
+            )
+            .add_systems(
+                PostUpdate,
+                QueryView::<T>::write_back
+                    .before(QueryParams::update_browser)
+                    .run_if(resource_changed::<QueryView<T>>),
+            )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy_ecs::system::RunSystemOnce;
+
+    crate::query_params! {
+        #[derive(Default, Debug, PartialEq)]
+        struct Filters {
+            tag: Vec<String>,
+            page: Option<u32>,
+            sort: String,
+        }
+    }
+
+    #[test]
+    fn test_multiple_values() {
+        let mut query: QueryParams = [("tag", "a"), ("q", "x"), ("tag", "b")]
+            .into_iter()
+            .collect();
+
+        assert_eq!(query.get("tag"), Some("a"));
+        assert_eq!(query.get_all("tag").collect::<Vec<_>>(), ["a", "b"]);
+
+        query.set("tag", "c");
+        assert_eq!(query.iter().collect::<Vec<_>>(), [("tag", "c"), ("q", "x")]);
+
+        query.append("tag", "d");
+        query.remove("q");
+        assert_eq!(query.get_all("tag").collect::<Vec<_>>(), ["c", "d"]);
+    }
+
+    #[test]
+    fn test_decode_encode() {
+        let mut query: QueryParams = [("tag", "a"), ("sort", "new"), ("tag", "b"), ("x", "1")]
+            .into_iter()
+            .collect();
+
+        let filters = query.decode::<Filters>().unwrap();
+        assert_eq!(
+            filters,
+            Filters {
+                tag: vec!["a".into(), "b".into()],
+                page: None,
+                sort: "new".into(),
+            }
+        );
+
+        query.encode(&Filters {
+            tag: vec!["c".into()],
+            page: Some(2),
+            sort: "old".into(),
+        });
+        assert_eq!(
+            query.iter().collect::<Vec<_>>(),
+            [("sort", "old"), ("x", "1"), ("tag", "c"), ("page", "2")]
+        );
+
+        let query: QueryParams = [("page", "two"), ("sort", "new")].into_iter().collect();
+        assert!(matches!(
+            query.decode::<Filters>(),
+            Err(QueryError::Invalid { key: "page", .. })
+        ));
+
+        let query = QueryParams::default();
+        assert_eq!(query.decode::<Filters>(), Err(QueryError::Missing("sort")));
+    }
+
+    #[test]
+    fn test_query_view() {
+        let mut world = World::new();
+        world.insert_resource::<QueryParams>(
+            [("sort", "new"), ("page", "01")].into_iter().collect(),
+        );
+        world.insert_resource(QueryView::<Filters>::new(QueryUpdate::Push));
+
+        world.run_system_once(QueryView::<Filters>::sync).unwrap();
+        assert_eq!(world.resource::<QueryView<Filters>>().page, Some(1));
+
+        // Syncing alone doesn't write back, even where the encoding differs.
+        world
+            .run_system_once(QueryView::<Filters>::write_back)
+            .unwrap();
+        assert_eq!(world.resource::<QueryParams>().get("page"), Some("01"));
+
+        world
+            .resource_mut::<QueryView<Filters>>()
+            .tag
+            .push("a".into());
+        world
+            .run_system_once(QueryView::<Filters>::write_back)
+            .unwrap();
+
+        let query = world.resource::<QueryParams>();
+        assert_eq!(query.get("tag"), Some("a"));
+        assert_eq!(query.get("page"), Some("1"));
+        assert_eq!(query.next_update, QueryUpdate::Push);
+    }
+}
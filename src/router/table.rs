@@ -0,0 +1,390 @@
+//! Listing registered routes, and expanding them into a sitemap.
+
+use super::{KeptAlive, MatchedRoute, PathSegment, Route, RouteViewOf, RouterPath};
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::{HashMap, HashSet};
+use core::fmt;
+
+impl Route {
+    /// The route patterns, in the order they were added.
+    pub fn paths(&self) -> impl Iterator<Item = &RouterPath> {
+        self.routes.iter().map(|r| &r.path)
+    }
+
+    /// The route patterns with their positions, in the order they're tried:
+    /// most specific first.
+    pub fn ranked(&self) -> Vec<(usize, &RouterPath)> {
+        let mut routes: Vec<_> = self.paths().enumerate().collect();
+        routes.sort_by(|a, b| a.1.cmp_specificity(b.1).reverse());
+        routes
+    }
+}
+
+impl RouterPath {
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+
+    /// The pattern matching `child` after this one, as for a [`Route`] nested
+    /// in this route's element.
+    pub fn join(&self, child: &RouterPath) -> RouterPath {
+        let segments: Vec<_> = self
+            .0
+            .iter()
+            .chain(&child.0)
+            .filter(|s| **s != PathSegment::Root)
+            .cloned()
+            .collect();
+
+        if segments.is_empty() {
+            RouterPath(vec![PathSegment::Root])
+        } else {
+            RouterPath(segments)
+        }
+    }
+
+    /// Every concrete path this pattern matches with the given parameter
+    /// values.
+    ///
+    /// A dynamic segment without values leaves the pattern with no paths.
+    /// Optional segments and catch-alls are expanded both with and without
+    /// their values.
+    pub fn expand(&self, params: &HashMap<String, Vec<String>>) -> Vec<String> {
+        let mut paths = vec![String::new()];
+
+        for segment in &self.0 {
+            let values = |name: &str| params.get(name).map(Vec::as_slice).unwrap_or_default();
+
+            paths = match segment {
+                PathSegment::Root => continue,
+                PathSegment::Static(s) => paths.into_iter().map(|p| format!("{p}/{s}")).collect(),
+                PathSegment::Param(name) => product(&paths, values(name), false),
+                PathSegment::Optional(name) => {
+                    let mut expanded = paths.clone();
+                    expanded.extend(product(&paths, values(name), false));
+                    expanded
+                }
+                PathSegment::CatchAll(name) => {
+                    let mut expanded = paths.clone();
+                    expanded.extend(product(&paths, values(name), true));
+                    expanded
+                }
+            };
+        }
+
+        let mut seen = HashSet::new();
+        paths
+            .into_iter()
+            .map(|p| if p.is_empty() { "/".to_string() } else { p })
+            .filter(|p| seen.insert(p.clone()))
+            .collect()
+    }
+}
+
+/// Each of `paths` followed by each of `values`.
+fn product(paths: &[String], values: &[String], keep_slashes: bool) -> Vec<String> {
+    paths
+        .iter()
+        .flat_map(|p| {
+            values
+                .iter()
+                .filter(|v| !v.is_empty())
+                .map(move |v| format!("{p}/{}", encode_segment(v, keep_slashes)))
+        })
+        .collect()
+}
+
+/// Percent-encode what can't appear in a path segment as is.
+fn encode_segment(value: &str, keep_slashes: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if keep_slashes => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Root => Ok(()),
+            Self::Static(s) => f.write_str(s),
+            Self::Param(name) => write!(f, ":{name}"),
+            Self::Optional(name) => write!(f, ":{name}?"),
+            Self::CatchAll(name) => write!(f, "*{name}"),
+        }
+    }
+}
+
+impl fmt::Display for RouterPath {
+    /// Write the pattern as it would be passed to [`Route::route`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.iter().all(|s| *s == PathSegment::Root) {
+            return f.write_str("/");
+        }
+
+        for segment in &self.0 {
+            write!(f, "/{segment}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A route in the app, as listed by [`route_table`].
+#[derive(Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct RouteTableEntry {
+    /// The `Route` entity.
+    pub route: Entity,
+    /// The route's position in its [`Route`], in the order the routes were
+    /// added.
+    pub index: usize,
+    /// The route's position in the order its [`Route`] tries them.
+    pub rank: usize,
+    /// The full pattern, including those of the routes this one's nested in.
+    pub path: RouterPath,
+}
+
+/// List the routes of every [`Route`] in the world.
+///
+/// Routes nested in an element only exist while it's shown, so only those
+/// in shown or kept-alive elements are listed.
+pub fn route_table(world: &mut World) -> Vec<RouteTableEntry> {
+    let routes: Vec<_> = world
+        .query_filtered::<Entity, With<Route>>()
+        .iter(world)
+        .collect();
+
+    let mut table = Vec::new();
+    for entity in routes {
+        let prefix = route_prefix(world, entity);
+        let route = world.get::<Route>(entity).unwrap();
+
+        for (rank, (index, path)) in route.ranked().into_iter().enumerate() {
+            table.push(RouteTableEntry {
+                route: entity,
+                index,
+                rank,
+                path: prefix.join(path),
+            });
+        }
+    }
+
+    table
+}
+
+/// The pattern of the routes `entity` is nested in.
+fn route_prefix(world: &World, entity: Entity) -> RouterPath {
+    let mut prefix = RouterPath(vec![PathSegment::Root]);
+    let mut current = world.get::<ChildOf>(entity).map(ChildOf::parent);
+
+    while let Some(node) = current {
+        // A kept-alive view stands in for its route entity.
+        let (route, index) = match (world.get::<RouteViewOf>(node), world.get::<KeptAlive>(node)) {
+            (Some(view), Some(kept_alive)) => (view.0, Some(kept_alive.index)),
+            _ => (
+                node,
+                world.get::<MatchedRoute>(node).map(MatchedRoute::index),
+            ),
+        };
+
+        if let Some(path) = index
+            .and_then(|i| world.get::<Route>(route)?.routes.get(i))
+            .map(|r| &r.path)
+        {
+            prefix = path.join(&prefix);
+        }
+
+        current = world.get::<ChildOf>(route).map(ChildOf::parent);
+    }
+
+    prefix
+}
+
+/// A `sitemap.xml` for a set of routes, built without a browser.
+///
+/// ```ignore
+/// let xml = Sitemap::new("https://example.com")
+///     .routes(route_table(world).iter().map(|entry| &entry.path))
+///     .param("id", ["1", "2"])
+///     .to_xml();
+/// ```
+#[derive(Default, Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct Sitemap {
+    base_url: String,
+    paths: Vec<RouterPath>,
+    params: HashMap<String, Vec<String>>,
+}
+
+impl Sitemap {
+    /// List URLs under `base_url`, like `https://example.com/app`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        while base_url.ends_with('/') {
+            base_url.pop();
+        }
+
+        Self {
+            base_url,
+            ..Default::default()
+        }
+    }
+
+    pub fn route(mut self, path: &RouterPath) -> Self {
+        self.paths.push(path.clone());
+        self
+    }
+
+    pub fn routes<'a>(mut self, paths: impl IntoIterator<Item = &'a RouterPath>) -> Self {
+        self.paths.extend(paths.into_iter().cloned());
+        self
+    }
+
+    /// Substitute each of `values` for the dynamic segments named `name`.
+    pub fn param<V: Into<String>>(
+        mut self,
+        name: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        self.params
+            .entry(name.into())
+            .or_default()
+            .extend(values.into_iter().map(Into::into));
+        self
+    }
+
+    /// The absolute URLs of every path the routes expand to, without
+    /// duplicates.
+    pub fn urls(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+
+        self.paths
+            .iter()
+            .flat_map(|path| path.expand(&self.params))
+            .filter(|path| seen.insert(path.clone()))
+            .map(|path| format!("{}{path}", self.base_url))
+            .collect()
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+        ));
+
+        for url in self.urls() {
+            xml.push_str("  <url><loc>");
+            for c in url.chars() {
+                match c {
+                    '&' => xml.push_str("&amp;"),
+                    '<' => xml.push_str("&lt;"),
+                    '>' => xml.push_str("&gt;"),
+                    '"' => xml.push_str("&quot;"),
+                    '\'' => xml.push_str("&apos;"),
+                    c => xml.push(c),
+                }
+            }
+            xml.push_str("</loc></url>\n");
+        }
+
+        xml.push_str("</urlset>\n");
+        xml
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dom::prelude::Body;
+    use crate::router::{Pathname, RouteParams, resolve_routes};
+
+    fn path(pattern: &'static str) -> RouterPath {
+        RouterPath::from_static(pattern).unwrap()
+    }
+
+    #[test]
+    fn test_display_and_join() {
+        for pattern in ["/", "/posts/:id", "/docs/:lang?/*rest", "/files/*"] {
+            assert_eq!(path(pattern).to_string(), pattern);
+        }
+
+        assert_eq!(
+            path("/outer").join(&path("/inner/:id")).to_string(),
+            "/outer/inner/:id"
+        );
+        assert_eq!(path("/").join(&path("/about")).to_string(), "/about");
+        assert_eq!(path("/outer").join(&path("/")).to_string(), "/outer");
+    }
+
+    #[test]
+    fn test_sitemap() {
+        let routes = Route::new()
+            .route("/", || ())
+            .route("/posts/:id", || ())
+            .route("/docs/:lang?", || ())
+            .route("/tags/:tag", || ());
+
+        let sitemap = Sitemap::new("https://example.com/")
+            .routes(routes.paths())
+            .param("id", ["1", "2"])
+            .param("lang", ["en"]);
+
+        assert_eq!(
+            sitemap.urls(),
+            [
+                "https://example.com/",
+                "https://example.com/posts/1",
+                "https://example.com/posts/2",
+                "https://example.com/docs",
+                "https://example.com/docs/en",
+            ]
+        );
+
+        let xml = Sitemap::new("https://example.com")
+            .route(&path("/search/:q"))
+            .param("q", ["a&b"])
+            .to_xml();
+        assert!(xml.contains("<loc>https://example.com/search/a%26b</loc>"));
+        assert!(xml.starts_with("<?xml"));
+    }
+
+    #[test]
+    fn test_route_table() {
+        let mut world = World::new();
+        world.init_resource::<RouteParams>();
+
+        let body = world.spawn(Body).id();
+        let outer = world
+            .spawn((
+                ChildOf(body),
+                Route::new().route("/about", || ()).route("/outer", || {
+                    children![Route::new().route("/inner/:id", || ())]
+                }),
+            ))
+            .id();
+
+        world.insert_resource(Pathname {
+            previous_path: None,
+            pathname: "/outer/inner/1".into(),
+        });
+        resolve_routes(&mut world).unwrap();
+        let inner = world.get::<Children>(outer).unwrap()[0];
+
+        let table: Vec<_> = route_table(&mut world)
+            .into_iter()
+            .map(|entry| (entry.route, entry.rank, entry.path.to_string()))
+            .collect();
+
+        assert!(table.contains(&(outer, 0, "/about".to_string())));
+        assert!(table.contains(&(outer, 1, "/outer".to_string())));
+        assert!(table.contains(&(inner, 0, "/outer/inner/:id".to_string())));
+        assert_eq!(table.len(), 3);
+    }
+}
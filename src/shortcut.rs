@@ -0,0 +1,881 @@
+//! Keyboard shortcuts, like `Ctrl+Shift+K`, `Mod+S` or `g g`, bound to ECS
+//! events and systems.
+//!
+//! A [`Shortcut`] on its own applies anywhere in the document. Related to an
+//! element through [`ShortcutOf`], it only applies while focus is within that
+//! element's entity subtree, and takes precedence over shortcuts further out.
+//!
+//! Shortcuts are opt-in: add [`ShortcutPlugin`] alongside
+//! [`BwebPlugins`](crate::BwebPlugins).
+//!
+//! ```ignore
+//! app.add_plugins((BwebPlugins, ShortcutPlugin));
+//!
+//! commands.spawn(Shortcut::run("Mod+S", save).with_description("Save"));
+//! commands.spawn((
+//!     Div,
+//!     related!(Shortcuts [Shortcut::trigger("g g", ScrollToTop)]),
+//! ));
+//! ```
+
+use crate::dom::html::{Navigator, NodeLookup, Window};
+use crate::dom::prelude::*;
+use crate::js_err::JsErr;
+use crate::runner::ScheduleTrigger;
+use bevy_app::prelude::*;
+use bevy_ecs::{lifecycle::HookContext, prelude::*, system::SystemId, world::DeferredWorld};
+use core::{fmt, ops::Deref, str::FromStr};
+use std::{borrow::Cow, time::Duration};
+use wasm_bindgen::JsCast;
+
+#[derive(Default)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct ShortcutPlugin;
+
+impl Plugin for ShortcutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShortcutSettings>()
+            .init_resource::<ShortcutState>()
+            .init_resource::<ActiveShortcuts>()
+            .add_observer(listen)
+            .add_systems(PostUpdate, (register_shortcuts, update_active).chain());
+    }
+}
+
+/// How shortcuts are read.
+#[derive(Resource, Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct ShortcutSettings {
+    /// Whether `Mod` means Cmd rather than Ctrl. This is detected from the
+    /// browser when the window is initialized, unless
+    /// [`detect_platform`](Self::detect_platform) is off.
+    pub mac: bool,
+    /// Whether to set [`mac`](Self::mac) from the browser. Turn it off to
+    /// choose for the user.
+    pub detect_platform: bool,
+    /// How long the next key of a sequence like `g g` is waited for.
+    pub sequence_timeout: Duration,
+}
+
+impl Default for ShortcutSettings {
+    fn default() -> Self {
+        Self {
+            mac: false,
+            detect_platform: true,
+            sequence_timeout: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Why a shortcut string couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShortcutError {
+    /// The string, or one of its keys, is empty.
+    Empty,
+    UnknownModifier(String),
+}
+
+impl fmt::Display for ShortcutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("empty shortcut key"),
+            Self::UnknownModifier(modifier) => write!(f, "unknown shortcut modifier `{modifier}`"),
+        }
+    }
+}
+
+impl core::error::Error for ShortcutError {}
+
+/// One key with its modifiers, like `Ctrl+Shift+K`.
+///
+/// Letters and digits match by the physical key as well as the character
+/// typed, so `Alt+K` works on macOS. Other characters only match by what's
+/// typed, and imply whatever Shift it takes: `?` matches `Shift+/`.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(any(test, feature = "debug"), derive(Debug))]
+pub struct KeyCombo {
+    key: String,
+    ctrl: bool,
+    shift: bool,
+    alt: bool,
+    meta: bool,
+    /// `Mod`: Cmd on macOS, Ctrl elsewhere.
+    primary: bool,
+}
+
+impl KeyCombo {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    fn is_symbol(&self) -> bool {
+        let mut chars = self.key.chars();
+        matches!((chars.next(), chars.next()), (Some(c), None) if !c.is_alphanumeric())
+    }
+
+    /// Whether typing into an input would press this.
+    fn is_typing(&self) -> bool {
+        self.key.chars().count() == 1 && !(self.ctrl || self.alt || self.meta || self.primary)
+    }
+
+    fn matches(&self, press: &Press, mac: bool) -> bool {
+        let key = |key: &str| key.eq_ignore_ascii_case(&self.key);
+        if !key(&press.key) && !press.code.as_deref().is_some_and(key) {
+            return false;
+        }
+
+        let (ctrl, meta) = match (self.primary, mac) {
+            (true, true) => (self.ctrl, true),
+            (true, false) => (true, self.meta),
+            (false, _) => (self.ctrl, self.meta),
+        };
+
+        ctrl == press.ctrl
+            && meta == press.meta
+            && self.alt == press.alt
+            && (self.shift == press.shift || (!self.shift && self.is_symbol()))
+    }
+
+    fn write(&self, f: &mut impl fmt::Write, mac: Option<bool>) -> fmt::Result {
+        let modifiers = [
+            (
+                self.primary,
+                mac.map_or("Mod", |mac| if mac { "Cmd" } else { "Ctrl" }),
+            ),
+            (self.ctrl, "Ctrl"),
+            (self.alt, "Alt"),
+            (self.shift, "Shift"),
+            (self.meta, if mac == Some(true) { "Cmd" } else { "Meta" }),
+        ];
+
+        let mut modified = false;
+        for (_, name) in modifiers.iter().filter(|(held, _)| *held) {
+            write!(f, "{name}+")?;
+            modified = true;
+        }
+
+        if modified && self.key.chars().count() == 1 {
+            write!(f, "{}", self.key.to_uppercase())
+        } else {
+            f.write_str(&self.key)
+        }
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = ShortcutError;
+
+    fn from_str(combo: &str) -> Result<Self, Self::Err> {
+        let (modifiers, key) = combo.rsplit_once('+').unwrap_or(("", combo));
+        let mut combo = Self {
+            key: normalize_key(key).ok_or(ShortcutError::Empty)?,
+            ..Default::default()
+        };
+
+        // An uppercase letter is pressed with Shift, as in `G`.
+        if modifiers.is_empty() && key.len() == 1 && key.chars().all(|c| c.is_ascii_uppercase()) {
+            combo.shift = true;
+        }
+
+        for modifier in modifiers.split('+').filter(|_| !modifiers.is_empty()) {
+            let held = match modifier.to_ascii_lowercase().as_str() {
+                "" => return Err(ShortcutError::Empty),
+                "ctrl" | "control" => &mut combo.ctrl,
+                "shift" => &mut combo.shift,
+                "alt" | "option" => &mut combo.alt,
+                "meta" | "cmd" | "command" | "super" | "win" => &mut combo.meta,
+                "mod" => &mut combo.primary,
+                _ => return Err(ShortcutError::UnknownModifier(modifier.to_string())),
+            };
+            *held = true;
+        }
+
+        Ok(combo)
+    }
+}
+
+/// The name [`KeyCombo`] uses for a key, given as it's written in a shortcut
+/// or reported by `KeyboardEvent.key`.
+fn normalize_key(key: &str) -> Option<String> {
+    let key = match key.to_ascii_lowercase().as_str() {
+        "" => return None,
+        " " | "space" | "spacebar" => "Space",
+        "esc" => "Escape",
+        "return" => "Enter",
+        "del" => "Delete",
+        "up" => "ArrowUp",
+        "down" => "ArrowDown",
+        "left" => "ArrowLeft",
+        "right" => "ArrowRight",
+        "plus" => "+",
+        lower if lower.chars().count() == 1 => return Some(lower.to_string()),
+        _ => key,
+    };
+
+    Some(key.to_string())
+}
+
+/// The keys of a shortcut, pressed one after the other, like `g g` or
+/// `Ctrl+K Ctrl+S`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(any(test, feature = "debug"), derive(Debug))]
+pub struct KeySequence(Vec<KeyCombo>);
+
+impl KeySequence {
+    pub fn parse(sequence: &str) -> Result<Self, ShortcutError> {
+        let combos = sequence
+            .split_whitespace()
+            .map(KeyCombo::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if combos.is_empty() {
+            return Err(ShortcutError::Empty);
+        }
+
+        Ok(Self(combos))
+    }
+
+    pub fn combos(&self) -> &[KeyCombo] {
+        &self.0
+    }
+
+    /// The sequence as it's pressed on this platform, like `Cmd+S` for
+    /// `Mod+S` on macOS.
+    pub fn label(&self, mac: bool) -> String {
+        let mut label = String::new();
+        // Writing to a `String` can't fail.
+        let _ = self.write(&mut label, Some(mac));
+        label
+    }
+
+    fn write(&self, f: &mut impl fmt::Write, mac: Option<bool>) -> fmt::Result {
+        for (i, combo) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_char(' ')?;
+            }
+            combo.write(f, mac)?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether `presses` are the start of this sequence.
+    fn starts_with(&self, presses: &[Press], mac: bool) -> bool {
+        self.0.len() >= presses.len() && self.0.iter().zip(presses).all(|(c, p)| c.matches(p, mac))
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = ShortcutError;
+
+    fn from_str(sequence: &str) -> Result<Self, Self::Err> {
+        Self::parse(sequence)
+    }
+}
+
+impl fmt::Display for KeySequence {
+    /// Write the sequence as it would be passed to [`Shortcut::run`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, None)
+    }
+}
+
+/// A key press, as read from a `keydown` event.
+#[derive(Clone)]
+#[cfg_attr(any(test, feature = "debug"), derive(Debug))]
+struct Press {
+    key: String,
+    /// The letter or digit of the physical key.
+    code: Option<String>,
+    ctrl: bool,
+    shift: bool,
+    alt: bool,
+    meta: bool,
+}
+
+impl Press {
+    /// Modifier keys on their own aren't presses.
+    fn from_event(ev: &web_sys::KeyboardEvent) -> Option<Self> {
+        let key = ev.key();
+        if matches!(
+            key.as_str(),
+            "Control" | "Shift" | "Alt" | "Meta" | "AltGraph" | "CapsLock" | "Dead"
+        ) {
+            return None;
+        }
+
+        let code = ev.code();
+        let code = code
+            .strip_prefix("Key")
+            .or_else(|| code.strip_prefix("Digit"))
+            .map(str::to_ascii_lowercase);
+
+        Some(Self {
+            key: normalize_key(&key)?,
+            code,
+            ctrl: ev.ctrl_key(),
+            shift: ev.shift_key(),
+            alt: ev.alt_key(),
+            meta: ev.meta_key(),
+        })
+    }
+}
+
+/// A keyboard shortcut, which triggers an event or runs a system.
+///
+/// Shortcuts are written as keys joined with `+`, like `Ctrl+Shift+K`, with
+/// spaces between the keys of a sequence, like `g g`. `Mod` is Cmd on macOS
+/// and Ctrl elsewhere. Shortcuts typed as plain characters, like `g g` or `?`,
+/// don't apply while an input has focus.
+#[derive(Component)]
+#[component(on_replace = Self::on_replace_hook)]
+pub struct Shortcut {
+    keys: KeySequence,
+    description: Option<Cow<'static, str>>,
+    action: Action,
+}
+
+enum Action {
+    Register(Option<Box<dyn FnOnce(&mut World) -> SystemId + Send + Sync>>),
+    System(SystemId),
+    Trigger(Box<dyn Fn(&mut Commands) + Send + Sync>),
+}
+
+#[cfg(feature = "debug")]
+impl std::fmt::Debug for Shortcut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Shortcut")
+            .field("keys", &self.keys)
+            .field("description", &self.description)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Shortcut {
+    /// Run `system` when `keys` are pressed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `keys` isn't a well-formed shortcut. See [`Self::try_run`].
+    pub fn run<S, M>(keys: &str, system: S) -> Self
+    where
+        S: IntoSystem<(), (), M> + Send + Sync + 'static,
+    {
+        Self::try_run(keys, system).expect("shortcut should be well-formed")
+    }
+
+    /// Run `system` when `keys` are pressed, or fail if `keys` isn't a
+    /// well-formed shortcut.
+    pub fn try_run<S, M>(keys: &str, system: S) -> Result<Self, ShortcutError>
+    where
+        S: IntoSystem<(), (), M> + Send + Sync + 'static,
+    {
+        Self::new(
+            keys,
+            Action::Register(Some(Box::new(move |world: &mut World| {
+                world.register_system(system)
+            }))),
+        )
+    }
+
+    /// Trigger `event` when `keys` are pressed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `keys` isn't a well-formed shortcut. See [`Self::try_trigger`].
+    pub fn trigger<E>(keys: &str, event: E) -> Self
+    where
+        E: Event + Clone,
+        for<'a> E::Trigger<'a>: Default,
+    {
+        Self::try_trigger(keys, event).expect("shortcut should be well-formed")
+    }
+
+    /// Trigger `event` when `keys` are pressed, or fail if `keys` isn't a
+    /// well-formed shortcut.
+    pub fn try_trigger<E>(keys: &str, event: E) -> Result<Self, ShortcutError>
+    where
+        E: Event + Clone,
+        for<'a> E::Trigger<'a>: Default,
+    {
+        Self::new(
+            keys,
+            Action::Trigger(Box::new(move |commands: &mut Commands| {
+                commands.trigger(event.clone())
+            })),
+        )
+    }
+
+    fn new(keys: &str, action: Action) -> Result<Self, ShortcutError> {
+        Ok(Self {
+            keys: KeySequence::parse(keys)?,
+            description: None,
+            action,
+        })
+    }
+
+    /// Describe what the shortcut does, for listing in [`ActiveShortcuts`].
+    pub fn with_description(self, description: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            description: Some(description.into()),
+            ..self
+        }
+    }
+
+    pub fn keys(&self) -> &KeySequence {
+        &self.keys
+    }
+
+    fn on_replace_hook(mut world: DeferredWorld, context: HookContext) {
+        if let Some(Self {
+            action: Action::System(system),
+            ..
+        }) = world.get::<Self>(context.entity)
+        {
+            let system = *system;
+            world.commands().unregister_system(system);
+        }
+    }
+}
+
+/// Scope a [`Shortcut`] to this element's entity subtree.
+#[derive(Component)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+#[cfg_attr(feature = "reflect", reflect(Component))]
+#[relationship(relationship_target = Shortcuts)]
+pub struct ShortcutOf(pub Entity);
+
+#[derive(Component)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+#[cfg_attr(feature = "reflect", reflect(Component))]
+#[relationship_target(relationship = ShortcutOf, linked_spawn)]
+pub struct Shortcuts(Vec<Entity>);
+
+/// A shortcut that currently applies, as listed in [`ActiveShortcuts`].
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct ActiveShortcut {
+    /// The [`Shortcut`] entity.
+    pub shortcut: Entity,
+    pub keys: KeySequence,
+    /// The keys as they're pressed on this platform.
+    pub label: String,
+    pub description: Option<Cow<'static, str>>,
+    /// The element the shortcut is scoped to, if any.
+    pub scope: Option<Entity>,
+}
+
+/// The shortcuts that apply with the current focus, innermost scope first.
+///
+/// Shortcuts hidden by another with the same keys further in aren't listed.
+#[derive(Resource, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct ActiveShortcuts(Vec<ActiveShortcut>);
+
+impl Deref for ActiveShortcuts {
+    type Target = [ActiveShortcut];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(Resource, Default)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+struct ShortcutState {
+    /// The entity with focus.
+    focus: Option<Entity>,
+    /// The presses of a sequence so far.
+    pending: Vec<Press>,
+    /// Counts sequence timers, so a timer can tell whether it's the latest.
+    timer: u32,
+}
+
+fn listen(
+    trigger: On<Add, Window>,
+    navigator: Query<&Navigator>,
+    mut settings: ResMut<ShortcutSettings>,
+    mut commands: Commands,
+) {
+    let window = trigger.entity;
+    if settings.detect_platform
+        && let Ok(navigator) = navigator.get(window)
+    {
+        // Shortcuts still work without it, just with Ctrl for `Mod`.
+        settings.mac = match platform(navigator) {
+            Ok(platform) => is_apple(&platform),
+            Err(e) => {
+                log::warn!("failed to detect the platform: {e}");
+                false
+            }
+        };
+    }
+
+    commands.spawn((EventOf(window), ev::key_down(on_key_down).suppress()));
+    commands.spawn((EventOf(window), ev::focus_in(on_focus_in)));
+    commands.spawn((EventOf(window), ev::focus_out(on_focus_out)));
+}
+
+/// The browser's platform, from the user-agent client hints where they're
+/// supported, or else the deprecated `navigator.platform`.
+fn platform(navigator: &web_sys::Navigator) -> Result<String> {
+    let hints = js_sys::Reflect::get(navigator, &"userAgentData".into()).js_err()?;
+    if hints.is_object()
+        && let Some(platform) = js_sys::Reflect::get(&hints, &"platform".into())
+            .js_err()?
+            .as_string()
+        && !platform.is_empty()
+    {
+        return Ok(platform);
+    }
+
+    navigator.platform().js_err()
+}
+
+/// Whether `platform`, in either form [`platform`] reads, is one of Apple's.
+fn is_apple(platform: &str) -> bool {
+    let platform = platform.to_ascii_lowercase();
+    ["mac", "iphone", "ipad", "ios"]
+        .iter()
+        .any(|name| platform.contains(name))
+}
+
+fn register_shortcuts(world: &mut World) {
+    let mut shortcuts = world.query::<(Entity, &mut Shortcut)>();
+    let unregistered: Vec<_> = shortcuts
+        .iter_mut(world)
+        .filter_map(|(entity, mut shortcut)| match &mut shortcut.action {
+            Action::Register(register) => Some((entity, register.take()?)),
+            _ => None,
+        })
+        .collect();
+
+    for (entity, register) in unregistered {
+        let system = register(world);
+        world.get_mut::<Shortcut>(entity).unwrap().action = Action::System(system);
+    }
+}
+
+/// The shortcuts that apply with `focus` focused, innermost scope first, with
+/// the scope of each.
+fn in_scope<'a>(
+    shortcuts: &'a Query<(Entity, &Shortcut, Option<&ShortcutOf>)>,
+    parents: &Query<&ChildOf>,
+    focus: Option<Entity>,
+) -> Vec<(Entity, &'a Shortcut, Option<Entity>)> {
+    let path: Vec<_> = focus
+        .into_iter()
+        .flat_map(|focus| core::iter::once(focus).chain(parents.iter_ancestors(focus)))
+        .collect();
+
+    let mut shortcuts: Vec<_> = shortcuts
+        .iter()
+        .filter_map(|(entity, shortcut, scope)| {
+            let depth = match scope {
+                Some(scope) => path.iter().position(|e| *e == scope.0)?,
+                None => path.len(),
+            };
+            Some((depth, entity, shortcut, scope.map(|s| s.0)))
+        })
+        .collect();
+
+    shortcuts.sort_by_key(|(depth, ..)| *depth);
+    shortcuts
+        .into_iter()
+        .map(|(_, entity, shortcut, scope)| (entity, shortcut, scope))
+        .collect()
+}
+
+/// Add `press` to the `pending` sequence, returning the shortcut it
+/// completes, if any.
+///
+/// `pending` is left with the presses of a sequence still in progress. A
+/// shortcut that's complete wins over a longer one it starts, so `g` hides
+/// `g g`.
+fn advance<'a>(
+    pending: &mut Vec<Press>,
+    press: Press,
+    shortcuts: impl Iterator<Item = (Entity, &'a KeySequence)> + Clone,
+    mac: bool,
+) -> Option<Entity> {
+    pending.push(press);
+
+    loop {
+        let mut started = shortcuts
+            .clone()
+            .filter(|(_, keys)| keys.starts_with(pending, mac));
+
+        if let Some((entity, _)) = started
+            .clone()
+            .find(|(_, keys)| keys.0.len() == pending.len())
+        {
+            pending.clear();
+            return Some(entity);
+        }
+
+        if started.next().is_some() {
+            return None;
+        }
+
+        // Start over from the last press, as in `g x g g`.
+        if pending.len() <= 1 {
+            pending.clear();
+            return None;
+        }
+        pending.drain(..pending.len() - 1);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn on_key_down(
+    ev: Ev<web_sys::KeyboardEvent>,
+    lookup: NodeLookup,
+    shortcuts: Query<(Entity, &Shortcut, Option<&ShortcutOf>)>,
+    parents: Query<&ChildOf>,
+    settings: Res<ShortcutSettings>,
+    mut state: ResMut<ShortcutState>,
+    mut schedule: ResMut<ScheduleTrigger>,
+    mut commands: Commands,
+) {
+    let ev: &web_sys::KeyboardEvent = &ev;
+    if ev.is_composing() {
+        return;
+    }
+    let Some(press) = Press::from_event(ev) else {
+        return;
+    };
+
+    let typing = ev
+        .target()
+        .and_then(|target| target.dyn_into::<web_sys::HtmlElement>().ok())
+        .is_some_and(|element| {
+            element.is_content_editable()
+                || matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+        });
+
+    let shortcuts = in_scope(&shortcuts, &parents, lookup.event_target(ev));
+    let keys = shortcuts
+        .iter()
+        .filter(|(_, shortcut, _)| !(typing && shortcut.keys.0[0].is_typing()))
+        .map(|(entity, shortcut, _)| (*entity, &shortcut.keys));
+
+    let completed = advance(&mut state.pending, press, keys, settings.mac);
+
+    if let Some((_, shortcut, _)) = completed.and_then(|c| shortcuts.iter().find(|s| s.0 == c)) {
+        ev.prevent_default();
+        match &shortcut.action {
+            Action::System(system) => commands.run_system(*system),
+            Action::Trigger(trigger) => trigger(&mut commands),
+            // Not registered until the next update.
+            Action::Register(_) => {}
+        }
+        schedule.trigger();
+    } else if !state.pending.is_empty() {
+        ev.prevent_default();
+        state.timer = state.timer.wrapping_add(1);
+        let timer = state.timer;
+
+        crate::time::timeout(settings.sequence_timeout, move |app| {
+            let mut state = app.world_mut().resource_mut::<ShortcutState>();
+            if state.timer == timer {
+                state.pending.clear();
+            }
+        });
+    }
+}
+
+fn on_focus_in(ev: Ev<web_sys::FocusEvent>, lookup: NodeLookup, mut state: ResMut<ShortcutState>) {
+    let ev: &web_sys::FocusEvent = &ev;
+    state.focus = lookup.event_target(ev);
+}
+
+fn on_focus_out(ev: Ev<web_sys::FocusEvent>, mut state: ResMut<ShortcutState>) {
+    // Focus moving elsewhere is picked up by `focusin`.
+    if ev.related_target().is_none() {
+        state.focus = None;
+    }
+}
+
+fn update_active(
+    shortcuts: Query<(Entity, &Shortcut, Option<&ShortcutOf>)>,
+    parents: Query<&ChildOf>,
+    state: Res<ShortcutState>,
+    settings: Res<ShortcutSettings>,
+    mut active: ResMut<ActiveShortcuts>,
+) {
+    let mut seen = Vec::new();
+    let shortcuts: Vec<_> = in_scope(&shortcuts, &parents, state.focus)
+        .into_iter()
+        .filter(|(_, shortcut, _)| {
+            let hidden = seen.contains(&&shortcut.keys);
+            seen.push(&shortcut.keys);
+            !hidden
+        })
+        .map(|(entity, shortcut, scope)| ActiveShortcut {
+            shortcut: entity,
+            keys: shortcut.keys.clone(),
+            label: shortcut.keys.label(settings.mac),
+            description: shortcut.description.clone(),
+            scope,
+        })
+        .collect();
+
+    if active.0 != shortcuts {
+        active.0 = shortcuts;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn press(key: &str, modifiers: &str) -> Press {
+        Press {
+            key: normalize_key(key).unwrap(),
+            code: None,
+            ctrl: modifiers.contains('c'),
+            shift: modifiers.contains('s'),
+            alt: modifiers.contains('a'),
+            meta: modifiers.contains('m'),
+        }
+    }
+
+    fn keys(sequence: &str) -> KeySequence {
+        KeySequence::parse(sequence).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_label() {
+        for sequence in [
+            "Ctrl+Shift+K",
+            "Mod+S",
+            "g g",
+            "Ctrl+K Ctrl+S",
+            "Shift+G",
+            "?",
+        ] {
+            assert_eq!(keys(sequence).to_string(), sequence);
+        }
+
+        assert_eq!(keys("G"), keys("shift+g"));
+        assert_eq!(keys("esc").to_string(), "Escape");
+        assert_eq!(keys("Mod+Shift+P").label(true), "Cmd+Shift+P");
+        assert_eq!(keys("Mod+Shift+P").label(false), "Ctrl+Shift+P");
+
+        assert_eq!(KeySequence::parse(" "), Err(ShortcutError::Empty));
+        assert_eq!(KeySequence::parse("Ctrl++"), Err(ShortcutError::Empty));
+        assert_eq!(
+            KeySequence::parse("Hyper+K"),
+            Err(ShortcutError::UnknownModifier("Hyper".into()))
+        );
+        assert!(Shortcut::try_run("Hyper+K", || {}).is_err());
+        assert!(Shortcut::try_run("Ctrl+K", || {}).is_ok());
+    }
+
+    #[test]
+    fn test_matches() {
+        let save = &keys("Mod+S").0[0];
+        assert!(save.matches(&press("s", "m"), true));
+        assert!(!save.matches(&press("s", "c"), true));
+        assert!(save.matches(&press("s", "c"), false));
+        assert!(!save.matches(&press("s", "cs"), false));
+
+        assert!(keys("Ctrl+Shift+K").0[0].matches(&press("K", "cs"), false));
+        assert!(keys("?").0[0].matches(&press("?", "s"), false));
+        assert!(!keys("k").0[0].matches(&press("K", "s"), false));
+
+        let mut alt_k = press("˚", "a");
+        alt_k.code = Some("k".into());
+        assert!(keys("Alt+K").0[0].matches(&alt_k, true));
+    }
+
+    #[test]
+    fn test_sequences() {
+        let top = keys("g g");
+        let inbox = keys("g i");
+        let help = keys("?");
+        let (e1, e2, e3) = (
+            Entity::from_raw_u32(1).unwrap(),
+            Entity::from_raw_u32(2).unwrap(),
+            Entity::from_raw_u32(3).unwrap(),
+        );
+        let shortcuts = [(e1, &top), (e2, &inbox), (e3, &help)];
+
+        let mut pending = Vec::new();
+        let mut advance = |key| advance(&mut pending, press(key, ""), shortcuts.into_iter(), false);
+
+        assert_eq!(advance("g"), None);
+        assert_eq!(advance("g"), Some(e1));
+        assert_eq!(advance("g"), None);
+        assert_eq!(advance("x"), None);
+        assert_eq!(advance("g"), None);
+        assert_eq!(advance("i"), Some(e2));
+        assert_eq!(advance("g"), None);
+        assert_eq!(advance("?"), Some(e3));
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_apple_platforms() {
+        assert!(is_apple("macOS"));
+        assert!(is_apple("MacIntel"));
+        assert!(is_apple("iPhone"));
+        assert!(!is_apple("Windows"));
+        assert!(!is_apple("Win32"));
+        assert!(!is_apple("Linux x86_64"));
+        assert!(!is_apple("Chrome OS"));
+    }
+
+    #[derive(Event, Clone)]
+    struct Save;
+
+    #[test]
+    fn test_active_shortcuts() {
+        let mut world = World::new();
+        world.init_resource::<ShortcutSettings>();
+        world.init_resource::<ShortcutState>();
+        world.init_resource::<ActiveShortcuts>();
+
+        let editor = world.spawn_empty().id();
+        let input = world.spawn(ChildOf(editor)).id();
+        let elsewhere = world.spawn_empty().id();
+
+        let save = world
+            .spawn(Shortcut::trigger("Mod+S", Save).with_description("Save"))
+            .id();
+        let top = world.spawn(Shortcut::run("g g", || {})).id();
+        let save_draft = world
+            .spawn((ShortcutOf(editor), Shortcut::trigger("Mod+S", Save)))
+            .id();
+        world.spawn((ShortcutOf(elsewhere), Shortcut::run("Escape", || {})));
+
+        let active = |world: &mut World| -> Vec<(Entity, String)> {
+            world.run_system_cached(update_active).unwrap();
+            world
+                .resource::<ActiveShortcuts>()
+                .iter()
+                .map(|active| (active.shortcut, active.label.clone()))
+                .collect()
+        };
+
+        assert_eq!(
+            active(&mut world),
+            [(save, "Ctrl+S".into()), (top, "g g".into())]
+        );
+
+        world.resource_mut::<ShortcutState>().focus = Some(input);
+        world.resource_mut::<ShortcutSettings>().mac = true;
+        assert_eq!(
+            active(&mut world),
+            [(save_draft, "Cmd+S".into()), (top, "g g".into())]
+        );
+
+        world.run_system_cached(register_shortcuts).unwrap();
+        assert!(matches!(
+            world.get::<Shortcut>(top).unwrap().action,
+            Action::System(_)
+        ));
+    }
+}
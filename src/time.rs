@@ -1,5 +1,20 @@
+use bevy_app::App;
 use std::time::Duration;
 
 pub async fn sleep(duration: Duration) {
     gloo_timers::future::sleep(duration).await
 }
+
+/// Run `func` with the app once `duration` has passed.
+pub(crate) fn timeout<F>(duration: Duration, func: F)
+where
+    F: FnOnce(&mut App) + 'static,
+{
+    wasm_bindgen_futures::spawn_local(async move {
+        sleep(duration).await;
+
+        if crate::runner::app_scope(func).is_err() {
+            log::error!("Failed to borrow app for timeout.");
+        }
+    });
+}